@@ -1,10 +1,76 @@
 use clap::{App, Arg, ArgMatches};
 use std::{io, fs};
 use std::io::{Result, Read};
+use std::str::FromStr;
+use std::path::{Path, PathBuf};
+
+use super::export::ExportFormat;
+use super::frontend::OutputFormat;
+use super::keys::{Control, Keybindings, parse_key};
+use super::theme::{Style, Theme};
+use super::tags::TagFilter;
+
+/// Environment variable with the path of the config file
+pub const CONFIG_VAR: &str = "PICKEM_CONFIG";
+
+/// Action taken when the typed keys don't match any chord
+#[derive(PartialEq, Debug, Clone)]
+pub enum DeadEndPolicy {
+    Ignore,
+    Clear,
+    Root,
+    Quit
+}
+
+impl DeadEndPolicy {
+    pub const NAMES: &'static [&'static str] = &["ignore", "clear", "root", "quit"];
+}
+
+impl FromStr for DeadEndPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(DeadEndPolicy::Ignore),
+            "clear" => Ok(DeadEndPolicy::Clear),
+            "root" => Ok(DeadEndPolicy::Root),
+            "quit" => Ok(DeadEndPolicy::Quit),
+            _ => Err(format!("{} is not a valid dead end policy", s))
+        }
+    }
+}
+
+/// Order in which the choices of a node are listed
+#[derive(PartialEq, Debug, Clone)]
+pub enum Order {
+    Chord,
+    /// Most often and recently picked first
+    Frecency,
+}
+
+impl Order {
+    pub const NAMES: &'static [&'static str] = &["chord", "frecency"];
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "chord" => Ok(Order::Chord),
+            "frecency" => Ok(Order::Frecency),
+            _ => Err(format!("{} is not a valid order", s))
+        }
+    }
+}
 
 pub struct Config<'a> {
     file: String,
-    matches: ArgMatches<'a>
+    matches: ArgMatches<'a>,
+    /// Defaults read from the config file, validated by `parse_settings`
+    settings: toml::Table,
+    keys: Keybindings,
+    theme: Theme,
 }
 
 fn parser() -> App<'static, 'static> {
@@ -16,25 +82,233 @@ fn parser() -> App<'static, 'static> {
              .help("Set input yaml file, '-' to read from stdin.")
              .required(true)
              .index(1))
+        .arg(Arg::with_name("config")
+             .short("c")
+             .long("config")
+             .takes_value(true)
+             .value_name("FILE")
+             .help("Reads defaults, keybindings and colors from FILE instead of $PICKEM_CONFIG or ~/.config/pickem/config.toml"))
         .arg(Arg::with_name("dryrun")
              .short("d")
              .long("dryrun")
              .required(false)
              .help("Performs a dry run by parsing the input yaml file, diplaying warnings and the final configuration"))
+        .arg(Arg::with_name("timeout")
+             .short("t")
+             .long("timeout")
+             .takes_value(true)
+             .value_name("MS")
+             .validator(|ms| ms.parse::<u64>().map(|_| ()).map_err(|err| err.to_string()))
+             .help("When a chord is the prefix of longer chords, wait MS milliseconds for more keys before picking it"))
+        .arg(Arg::with_name("exec")
+             .short("x")
+             .long("exec")
+             .required(false)
+             .help("Runs the value of picked leaves as a command instead of printing it"))
+        .arg(Arg::with_name("no-exec")
+             .long("no-exec")
+             .required(false)
+             .overrides_with("exec")
+             .help("Prints the value of picked leaves even if exec is set in the config file"))
+        .arg(Arg::with_name("launcher")
+             .short("l")
+             .long("launcher")
+             .required(false)
+             .help("Returns to the menu after running a command, showing its exit status"))
+        .arg(Arg::with_name("no-launcher")
+             .long("no-launcher")
+             .required(false)
+             .overrides_with("launcher")
+             .help("Quits after running a command even if launcher is set in the config file"))
+        .arg(Arg::with_name("shell")
+             .long("shell")
+             .takes_value(true)
+             .value_name("SHELL")
+             .help("Shell used to run commands, defaults to $SHELL"))
+        .arg(Arg::with_name("dead-end")
+             .long("dead-end")
+             .takes_value(true)
+             .value_name("POLICY")
+             .possible_values(DeadEndPolicy::NAMES)
+             .default_value("clear")
+             .help("What to do when the keys typed don't lead to any chord: ignore the last key, clear the typed keys, reset to the root or quit with an error"))
+        .arg(Arg::with_name("order")
+             .long("order")
+             .takes_value(true)
+             .value_name("ORDER")
+             .possible_values(Order::NAMES)
+             .default_value("chord")
+             .help("Lists choices by chord or with the most often and recently picked first"))
+        .arg(Arg::with_name("recent")
+             .long("recent")
+             .required(false)
+             .help("Adds a node with the last leaves picked to the root, bound to Tab"))
+        .arg(Arg::with_name("no-recent")
+             .long("no-recent")
+             .required(false)
+             .overrides_with("recent")
+             .help("Leaves out the node of the last leaves picked even if recent is set in the config file"))
+        .arg(Arg::with_name("no-history")
+             .long("no-history")
+             .required(false)
+             .help("Doesn't record picks"))
+        .arg(Arg::with_name("tag")
+             .long("tag")
+             .takes_value(true)
+             .value_name("TAG")
+             .multiple(true)
+             .number_of_values(1)
+             .help("Only shows the leaves tagged with TAG, can be given several times to show any of the tags"))
+        .arg(Arg::with_name("exclude-tag")
+             .long("exclude-tag")
+             .takes_value(true)
+             .value_name("TAG")
+             .multiple(true)
+             .number_of_values(1)
+             .help("Hides the leaves tagged with TAG, can be given several times"))
+        .arg(Arg::with_name("output")
+             .long("output")
+             .takes_value(true)
+             .value_name("FORMAT")
+             .possible_values(OutputFormat::NAMES)
+             .default_value("value")
+             .help("Prints the value of picked leaves or the signals of the selector"))
+        .arg(Arg::with_name("export")
+             .short("e")
+             .long("export")
+             .takes_value(true)
+             .value_name("FORMAT")
+             .possible_values(ExportFormat::NAMES)
+             .required(false)
+             .help("Prints the tree in the given format instead of running the selector"))
+        .arg(Arg::with_name("depth")
+             .long("depth")
+             .takes_value(true)
+             .value_name("N")
+             .requires("export")
+             .validator(|depth| depth.parse::<usize>().map(|_| ()).map_err(|err| err.to_string()))
+             .help("Limits the export to N levels below the root"))
+}
+
+/// Returns the path of the config file given by `--config`, then `$PICKEM_CONFIG`,
+/// then under `$XDG_CONFIG_HOME` or `~/.config`. Also returns whether it was
+/// explicitly given, in which case it must exist.
+fn config_file(matches: &ArgMatches) -> Option<(PathBuf, bool)> {
+    if let Some(file) = matches.value_of("config") {
+        return Some((PathBuf::from(file), true));
+    }
+    if let Some(file) = std::env::var_os(CONFIG_VAR).filter(|file| !file.is_empty()) {
+        return Some((PathBuf::from(file), true));
+    }
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some((config_home.join("pickem").join("config.toml"), false))
+}
+
+/// Parses and validates the settings of a config file, returning them along
+/// with the keybindings of its `[keys]` table and the colors of its `[colors]` table
+pub fn parse_settings(data: &str) -> std::result::Result<(toml::Table, Keybindings, Theme), String> {
+    let mut settings = data.parse::<toml::Table>().map_err(|err| err.to_string())?;
+    let mut keys = Keybindings::default();
+    let mut theme = Theme::default();
+    for (name, value) in settings.iter() {
+        let valid = match (name.as_str(), value) {
+            ("timeout", toml::Value::Integer(ms)) => *ms >= 0,
+            ("exec" | "launcher" | "recent" | "history", toml::Value::Boolean(_)) => true,
+            ("shell", toml::Value::String(_)) => true,
+            ("dead-end", toml::Value::String(policy)) => DeadEndPolicy::NAMES.contains(&policy.as_str()),
+            ("order", toml::Value::String(order)) => Order::NAMES.contains(&order.as_str()),
+            ("output", toml::Value::String(format)) => OutputFormat::NAMES.contains(&format.as_str()),
+            ("keys" | "colors", toml::Value::Table(_)) => true,
+            ("timeout" | "exec" | "launcher" | "recent" | "history" | "shell" | "dead-end" | "order" | "output" | "keys" | "colors", _) => false,
+            _ => return Err(format!("unknown setting {}", name)),
+        };
+        if !valid {
+            return Err(format!("invalid {} for {}, see pickem --help", value.type_str(), name));
+        }
+    }
+    if let Some(toml::Value::Table(bindings)) = settings.remove("keys") {
+        for (name, value) in bindings.iter() {
+            let control = name.parse::<Control>()?;
+            let names = match value {
+                toml::Value::String(key) => vec![key.as_str()],
+                toml::Value::Array(array) => array.iter()
+                    .map(|key| key.as_str().ok_or_else(|| format!("the keys of {} must be strings", name)))
+                    .collect::<std::result::Result<_, _>>()?,
+                _ => return Err(format!("the keys of {} must be a string or a list of strings", name)),
+            };
+            let bound = names.into_iter()
+                .map(parse_key)
+                .collect::<std::result::Result<_, _>>()?;
+            keys.bind(control, bound);
+        }
+    }
+    if let Some(toml::Value::Table(colors)) = settings.remove("colors") {
+        for (name, value) in colors.iter() {
+            let style = value.as_str()
+                .ok_or_else(|| format!("the style of {} must be a string, eg. \"bold red\"", name))?
+                .parse::<Style>()?;
+            theme.set(name, style)?;
+        }
+    }
+    Ok((settings, keys, theme))
 }
 
 impl Config<'_> {
 
-    ///Build Config from matches
-    pub fn from_env<'a>() -> Config<'a> {
+    ///Build Config from matches and the config file.
+    pub fn from_env<'a>() -> std::result::Result<Config<'a>, String> {
         let m = parser().get_matches();
+        let data = match config_file(&m) {
+            Some((file, explicit)) => match fs::read_to_string(&file) {
+                Ok(data) => data,
+                Err(err) if err.kind() == io::ErrorKind::NotFound && !explicit => String::new(),
+                Err(err) => return Err(format!("can't read {}: {}", file.display(), err)),
+            },
+            None => String::new(),
+        };
+        let (settings, keys, theme) = parse_settings(&data)?;
         //All values have defaults. As such unwrap do be safe though.
-        Config {
+        Ok(Config {
             file: String::from(m.value_of("INPUT").unwrap()),
-            matches: m
+            matches: m,
+            settings,
+            keys,
+            theme,
+        })
+    }
+
+    /// Returns the value of an option given on the command line, then in
+    /// the config file, then its default
+    fn value_of(&self, name: &str) -> Option<String> {
+        let setting = self.settings.get(name).and_then(|value| match value {
+            toml::Value::String(value) => Some(value.clone()),
+            toml::Value::Integer(value) => Some(value.to_string()),
+            _ => None,
+        });
+        if self.matches.occurrences_of(name) > 0 {
+            self.matches.value_of(name).map(String::from)
+        }
+        else {
+            setting.or_else(|| self.matches.value_of(name).map(String::from))
         }
     }
 
+    /// Returns whether a flag is given on the command line or set in the config file.
+    /// `--no-<flag>` unsets a flag set in the config file.
+    fn flag(&self, name: &str) -> bool {
+        if self.matches.is_present(format!("no-{}", name)) {
+            return false;
+        }
+        self.matches.is_present(name) || self.setting_flag(name).unwrap_or(false)
+    }
+
+    fn setting_flag(&self, name: &str) -> Option<bool> {
+        self.settings.get(name).and_then(toml::Value::as_bool)
+    }
+
     ///Returns the data to be used for pickem.
     pub fn raw_yaml(&self) -> Result<String> {
         if self.file.as_str() == "-" {
@@ -47,7 +321,137 @@ impl Config<'_> {
         }
     }
 
+    ///Returns the path of the menu, unless it's read from stdin.
+    pub fn menu_path(&self) -> Option<&Path> {
+        Some(Path::new(self.file.as_str())).filter(|_| self.file.as_str() != "-")
+    }
+
     pub fn is_dryrun(&self) -> bool {
         self.matches.is_present("dryrun")
     }
+
+    pub fn is_exec(&self) -> bool {
+        self.flag("exec")
+    }
+
+    pub fn is_launcher(&self) -> bool {
+        self.flag("launcher")
+    }
+
+    ///Returns the shell used to run commands, if set.
+    pub fn shell(&self) -> Option<String> {
+        self.value_of("shell")
+    }
+
+    ///Returns how long to wait for longer chords, if set.
+    pub fn chord_timeout(&self) -> Option<u64> {
+        self.value_of("timeout")
+            .and_then(|ms| ms.parse().ok())
+    }
+
+    ///Returns how dead ends should be handled.
+    pub fn dead_end_policy(&self) -> DeadEndPolicy {
+        self.value_of("dead-end")
+            .and_then(|policy| policy.parse().ok())
+            .unwrap_or(DeadEndPolicy::Clear)
+    }
+
+    ///Returns the order in which choices are listed.
+    pub fn order(&self) -> Order {
+        self.value_of("order")
+            .and_then(|order| order.parse().ok())
+            .unwrap_or(Order::Chord)
+    }
+
+    pub fn shows_recent(&self) -> bool {
+        self.flag("recent")
+    }
+
+    pub fn records_history(&self) -> bool {
+        !self.matches.is_present("no-history") && self.setting_flag("history").unwrap_or(true)
+    }
+
+    ///Returns how picks are printed.
+    pub fn output_format(&self) -> OutputFormat {
+        self.value_of("output")
+            .and_then(|format| format.parse().ok())
+            .unwrap_or(OutputFormat::Value)
+    }
+
+    ///Returns the tags leaves are filtered by.
+    pub fn tag_filter(&self) -> TagFilter {
+        let tags = |name: &str| self.matches.values_of(name)
+            .map(|tags| tags.map(String::from).collect())
+            .unwrap_or_default();
+        TagFilter { include: tags("tag"), exclude: tags("exclude-tag") }
+    }
+
+    pub fn keybindings(&self) -> Keybindings {
+        self.keys.clone()
+    }
+
+    ///Returns the theme of the config file, without the styles the terminal can't show.
+    pub fn theme(&self) -> Theme {
+        self.theme.clone().for_terminal()
+    }
+
+    ///Returns the format the tree should be exported to, if any.
+    pub fn export_format(&self) -> Option<ExportFormat> {
+        self.matches.value_of("export")
+            .and_then(|format| format.parse().ok())
+    }
+
+    ///Returns the maximum depth for exports, if any.
+    pub fn export_depth(&self) -> Option<usize> {
+        self.matches.value_of("depth")
+            .and_then(|depth| depth.parse().ok())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use termion::event::Key;
+
+    #[test]
+    fn test_parse_settings() {
+        let data = "
+timeout = 300
+order = \"frecency\"
+
+[keys]
+quit = [\"q\", \"esc\"]
+search = \"<C-s>\"
+
+[colors]
+chord = \"light-blue\"
+";
+        let (settings, keys, theme) = parse_settings(data).unwrap();
+        assert_eq!(settings.get("timeout").and_then(toml::Value::as_integer), Some(300));
+        assert!(settings.get("keys").is_none());
+        assert_eq!(keys.control(Key::Char('q')), Some(Control::Quit));
+        assert_eq!(keys.control(Key::Esc), Some(Control::Quit));
+        assert_eq!(keys.control(Key::Char('\n')), None);
+        assert_eq!(keys.control(Key::Ctrl('s')), Some(Control::Search));
+        assert_eq!(keys.control(Key::Char('/')), None);
+        assert_eq!(keys.control(Key::Backspace), Some(Control::Back));
+        assert_eq!(theme.chord, "light-blue".parse().unwrap());
+
+        assert!(parse_settings("timeout = -1").is_err());
+        assert!(parse_settings("order = \"random\"").is_err());
+        assert!(parse_settings("colour = \"red\"").is_err());
+        assert!(parse_settings("[keys]\nfly = \"f\"").is_err());
+        assert!(parse_settings("[colors]\nchord = \"purple\"").is_err());
+    }
+
+    #[test]
+    fn test_command_line_unsets_flags() {
+        let (settings, keys, theme) = parse_settings("exec = true\nlauncher = true\nrecent = true").unwrap();
+        let matches = parser().get_matches_from(vec!["pickem", "--no-exec", "--launcher", "--no-launcher", "menu.yml"]);
+        let config = Config { file: String::from("menu.yml"), matches, settings, keys, theme };
+        assert!(!config.is_exec());
+        assert!(!config.is_launcher());
+        assert!(config.shows_recent());
+    }
 }
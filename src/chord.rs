@@ -0,0 +1,168 @@
+use std::fmt;
+
+/// A single key pressed as part of a chord
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyToken {
+    Char(char),
+    Ctrl(char),
+    Alt(char),
+    F(u8),
+    Space,
+    Tab,
+}
+
+impl KeyToken {
+    /// Parses the inside of a `<...>` special key
+    fn from_name(name: &str) -> Option<KeyToken> {
+        let lower = name.to_lowercase();
+        if let Some((modifier, key)) = lower.split_once('-') {
+            let mut chars = key.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return None,
+            };
+            // Alt keeps case since <M-x> and <M-X> are different keys
+            let original = name.chars().last().unwrap();
+            return match modifier {
+                "c" | "ctrl" => Some(KeyToken::Ctrl(c)),
+                "m" | "a" | "alt" => Some(KeyToken::Alt(original)),
+                _ => None,
+            };
+        }
+        match lower.as_str() {
+            "space" => Some(KeyToken::Space),
+            "tab" => Some(KeyToken::Tab),
+            "lt" => Some(KeyToken::Char('<')),
+            _ => lower.strip_prefix('f')
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=12).contains(n))
+                .map(KeyToken::F),
+        }
+    }
+
+    /// Returns the key as shown to users, eg. `Ctrl+g`
+    pub fn readable(&self) -> String {
+        match self {
+            KeyToken::Char(c) => c.to_string(),
+            KeyToken::Ctrl(c) => format!("Ctrl+{}", c),
+            KeyToken::Alt(c) => format!("Alt+{}", c),
+            KeyToken::F(n) => format!("F{}", n),
+            KeyToken::Space => String::from("Space"),
+            KeyToken::Tab => String::from("Tab"),
+        }
+    }
+}
+
+impl From<char> for KeyToken {
+    fn from(c: char) -> Self {
+        match c {
+            ' ' => KeyToken::Space,
+            '\t' => KeyToken::Tab,
+            c => KeyToken::Char(c),
+        }
+    }
+}
+
+/// Formats the key in chord notation, such that parsing it returns the same key
+impl fmt::Display for KeyToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyToken::Char('<') => write!(f, "<lt>"),
+            KeyToken::Char(c) => write!(f, "{}", c),
+            KeyToken::Ctrl(c) => write!(f, "<C-{}>", c),
+            KeyToken::Alt(c) => write!(f, "<M-{}>", c),
+            KeyToken::F(n) => write!(f, "<F{}>", n),
+            KeyToken::Space => write!(f, "<Space>"),
+            KeyToken::Tab => write!(f, "<Tab>"),
+        }
+    }
+}
+
+
+/// Parses a chord into the keys that have to be pressed.
+/// Special keys are written between angle brackets: `<C-g>`, `<M-x>`, `<F5>`,
+/// `<Space>`, `<Tab>` and `<lt>` for a literal `<`.
+/// A `<` without a closing `>` is taken literally, an unknown special key is an error.
+pub fn parse(chord: &str) -> Result<Vec<KeyToken>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = chord;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                let name = &rest[1..end];
+                match KeyToken::from_name(name) {
+                    Some(token) => tokens.push(token),
+                    None => return Err(format!("<{}> is not a known key, use <lt> for a literal <", name)),
+                }
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        tokens.push(KeyToken::from(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(tokens)
+}
+
+/// Parses a chord, taking malformed special keys literally
+pub fn parse_lossy(chord: &str) -> Vec<KeyToken> {
+    parse(chord).unwrap_or_else(|_| chord.chars().map(KeyToken::from).collect())
+}
+
+/// Returns a chord as shown to users. Chords of plain characters are kept as is,
+/// chords with special keys have their keys separated by spaces, eg. `Ctrl+g s`.
+pub fn readable(chord: &str) -> String {
+    let (typed, rest) = readable_split(chord, 0);
+    typed + &rest
+}
+
+/// Splits the readable form of a chord after its first `typed` keys,
+/// eg. `Ctrl+g` and ` s` for `<C-g>s` after one key.
+pub fn readable_split(chord: &str, typed: usize) -> (String, String) {
+    let tokens = parse_lossy(chord);
+    let separator = match tokens.iter().all(|token| matches!(token, KeyToken::Char(_))) {
+        true => "",
+        false => " ",
+    };
+    let typed = typed.min(tokens.len());
+    let join = |tokens: &[KeyToken]| tokens.iter().map(KeyToken::readable).collect::<Vec<_>>().join(separator);
+    let mut rest = join(&tokens[typed..]);
+    if typed > 0 && !rest.is_empty() {
+        rest.insert_str(0, separator);
+    }
+    (join(&tokens[..typed]), rest)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("sx"), Ok(vec![KeyToken::Char('s'), KeyToken::Char('x')]));
+        assert_eq!(parse("<C-g>s"), Ok(vec![KeyToken::Ctrl('g'), KeyToken::Char('s')]));
+        assert_eq!(parse("<M-X><F5><space> \t"),
+                   Ok(vec![KeyToken::Alt('X'), KeyToken::F(5), KeyToken::Space, KeyToken::Space, KeyToken::Tab]));
+        assert_eq!(parse("<lt>a<"), Ok(vec![KeyToken::Char('<'), KeyToken::Char('a'), KeyToken::Char('<')]));
+        assert!(parse("<C-gg>").is_err());
+        assert!(parse("<F13>").is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let tokens = parse("<C-g><M-x><F12><Space><Tab><lt>a").unwrap();
+        let notation = tokens.iter().map(|token| token.to_string()).collect::<String>();
+        assert_eq!(notation, "<C-g><M-x><F12><Space><Tab><lt>a");
+        assert_eq!(parse(&notation), Ok(tokens));
+    }
+
+    #[test]
+    fn test_readable() {
+        assert_eq!(readable("sx"), "sx");
+        assert_eq!(readable("<C-g>s"), "Ctrl+g s");
+        assert_eq!(readable_split("sx", 1), (String::from("s"), String::from("x")));
+        assert_eq!(readable_split("<C-g>s<Tab>", 1), (String::from("Ctrl+g"), String::from(" s Tab")));
+        assert_eq!(readable_split("s", 1), (String::from("s"), String::new()));
+    }
+}
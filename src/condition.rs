@@ -0,0 +1,213 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use yaml_rust::Yaml;
+
+use super::exec::{self, Executor};
+use super::tree::{Tree, LeafData};
+
+/// Reserved key with the conditions under which a node is kept
+pub const WHEN_KEY: &str = ".when";
+/// Time a command condition has to finish before it's taken as failed
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+
+
+/// Condition on the machine pickem runs on
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// The environment variable is set, and has the given value if any
+    Env(String, Option<String>),
+    /// The file or directory exists, a leading `~` is the home directory
+    File(String),
+    /// The hostname matches the pattern, in which `*` matches any characters
+    Host(String),
+    /// The command exits with status 0 within `COMMAND_TIMEOUT`
+    Command(String),
+}
+
+impl Condition {
+    pub const NAMES: &'static [&'static str] = &["env", "file", "host", "command"];
+
+    /// Returns whether the condition holds, commands are run by `executor`
+    pub fn holds(&self, executor: &Executor) -> bool {
+        match self {
+            Condition::Env(name, value) => match (std::env::var(name), value) {
+                (Ok(actual), Some(value)) => actual == *value,
+                (Ok(_), None) => true,
+                (Err(_), _) => false,
+            },
+            Condition::File(path) => Path::new(&exec::expand_home(path)).exists(),
+            Condition::Host(pattern) => hostname().is_some_and(|host| matches_pattern(pattern, &host)),
+            Condition::Command(command) => succeeds(executor, command),
+        }
+    }
+
+    /// Writes the condition as it's written in `.when`
+    pub fn to_yaml(&self) -> String {
+        match self {
+            Condition::Env(name, None) => format!("env: {}", name),
+            Condition::Env(name, Some(value)) => format!("env: {{{}: {}}}", name, value),
+            Condition::File(path) => format!("file: {}", path),
+            Condition::Host(pattern) => format!("host: {}", pattern),
+            Condition::Command(command) => format!("command: {}", command),
+        }
+    }
+}
+
+
+/// Reads the conditions of a `.when` mapping, all of which must hold, eg.
+/// `{env: SSH_AUTH_SOCK, file: ~/.kube/config, host: "work-*", command: command -v docker}`.
+/// `env` also takes a mapping of variables to the values they must have.
+pub fn from_yaml(node: &Yaml) -> Result<Vec<Condition>, String> {
+    let hash = match node {
+        Yaml::BadValue => return Ok(Vec::new()),
+        Yaml::Hash(hash) => hash,
+        _ => return Err(format!("{} takes a mapping of conditions, eg. {{file: ~/.kube/config}}", WHEN_KEY)),
+    };
+    let mut conditions = Vec::new();
+    for (key, value) in hash.iter() {
+        match (key.as_str(), value) {
+            (Some("env"), Yaml::String(name)) => conditions.push(Condition::Env(name.clone(), None)),
+            (Some("env"), Yaml::Hash(vars)) => {
+                for (name, value) in vars.iter() {
+                    match (name.as_str(), value.as_str()) {
+                        (Some(name), Some(value)) => conditions.push(Condition::Env(String::from(name), Some(String::from(value)))),
+                        _ => return Err(String::from("environment variables and their values must be strings")),
+                    }
+                }
+            },
+            (Some("file"), Yaml::String(path)) => conditions.push(Condition::File(path.clone())),
+            (Some("host"), Yaml::String(pattern)) => conditions.push(Condition::Host(pattern.clone())),
+            (Some("command"), Yaml::String(command)) => conditions.push(Condition::Command(command.clone())),
+            (Some(name), _) if Condition::NAMES.contains(&name) => return Err(format!("the {} condition takes a string", name)),
+            _ => return Err(format!("conditions are {}", Condition::NAMES.join(", "))),
+        }
+    }
+    Ok(conditions)
+}
+
+/// Drops the trees whose conditions don't hold, along with their children
+pub fn prune(tree: Tree, executor: &Executor) -> Tree {
+    match tree {
+        Tree::Node(data, children) => {
+            let children = children.into_iter()
+                .filter(|child| child.data().when.iter().all(|condition| condition.holds(executor)))
+                .map(|child| prune(child, executor))
+                .collect();
+            Tree::Node(data, children)
+        },
+        leaf => leaf,
+    }
+}
+
+/// Runs a command condition, killing it if it doesn't finish in time
+/// so a hanging command doesn't keep the menu from showing up
+fn succeeds(executor: &Executor, command: &str) -> bool {
+    let mut child = match executor.command(&LeafData::default(), command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn() {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    let deadline = Instant::now() + COMMAND_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.success(),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return false;
+            },
+        }
+    }
+}
+
+/// Returns whether `text` matches `pattern`, in which `*` matches any characters
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((start, rest)) => text.strip_prefix(start).is_some_and(|text| {
+            (0..=text.len())
+                .filter(|index| text.is_char_boundary(*index))
+                .any(|index| matches_pattern(rest, &text[index..]))
+        }),
+    }
+}
+
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let end = buffer.iter().position(|byte| *byte == 0).unwrap_or(buffer.len());
+    Some(String::from_utf8_lossy(&buffer[..end]).into_owned())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("work-*", "work-laptop"));
+        assert!(matches_pattern("*-laptop", "work-laptop"));
+        assert!(matches_pattern("w*k*", "work-laptop"));
+        assert!(!matches_pattern("work-*", "home-laptop"));
+        assert!(!matches_pattern("work", "work-laptop"));
+    }
+
+    #[test]
+    fn test_prune_conditions() {
+        // tests run in parallel, so rather than setting a variable this uses PATH
+        let path = std::env::var("PATH").unwrap();
+        let yml = format!("
+kept:
+  .value: kept
+  .when:
+    env: {{PATH: '{}'}}
+    file: /
+    command: exit 0
+dropped:
+  .value: dropped
+  .when:
+    command: exit 1
+other:
+  .value: other
+  .when:
+    env: {{PATH: /no/such/dir}}
+slow:
+  .value: slow
+  .when:
+    command: sleep 5
+unset:
+  .value: unset
+  .when:
+    env: PICKEM_UNSET_VARIABLE
+nested:
+  .chord: n
+  dropped:
+    .value: dropped
+    .when:
+      file: /no/such/file
+", path.replace('\'', "''"));
+        let (tree, violations) = parser::parse(&yml);
+        assert!(violations.is_empty());
+        let tree = prune(tree, &Executor::new("/bin/sh"));
+        let children = tree.children();
+        assert_eq!(children.len(), 2);
+        assert!(children.contains_key(&"kept"));
+        assert!(children[&"n"].children().is_empty());
+
+        let (_, violations) = parser::parse("x:\n  .value: x\n  .when:\n    os: linux\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].child_name, WHEN_KEY);
+    }
+}
@@ -1,15 +1,53 @@
 use super::tree::Tree;
+use super::chord::{self, KeyToken};
+use super::search::{self, SearchState, SearchResult};
+use super::placeholder::{self, PromptState};
 
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum DriverFlag {
+    /// When the input buffer is a chord and also the prefix of longer chords,
+    /// wait for more input (or `DriverCommand::Resolve`) instead of picking it
+    ChordTimeout,
+    /// On a dead end, drop the key that caused it and keep the rest of the input buffer
+    IgnoreDeadEnd,
+    /// On a dead end, walk back to root. By default only the input buffer is cleared.
+    RootOnDeadEnd,
 }
 
 
 #[derive(Debug)]
 pub enum DriverCommand<'a> {
     Backtrack,
-    Transition(&'a str)
+    /// Walks back to root and clears the input buffer
+    Root,
+    /// Walks up `n` levels in the tree, stopping at root
+    Up(usize),
+    Transition(&'a str),
+    /// Starts a search or replaces the query of the current one
+    Search(&'a str),
+    /// Selects the n-th result of the current search
+    SelectResult(usize),
+    /// Walks to the selected search result
+    PickResult,
+    CancelSearch,
+    /// Picks the chord pending in the input buffer, if any
+    Resolve,
+    /// Reverts the last change to the path or selections
+    Undo,
+    /// Reapplies the last undone change
+    Redo,
+    /// Replaces what has been typed for the placeholder being asked for
+    Answer(&'a str),
+    /// Accepts the typed answer, or the default if nothing was typed.
+    /// The leaf is picked once every placeholder has an answer.
+    SubmitAnswer,
+    /// Stops asking for placeholders without picking the leaf
+    CancelPrompt,
+    /// Picks the leaf waiting for confirmation
+    Confirm,
+    /// Drops the leaf waiting for confirmation
+    Decline,
 }
 
 
@@ -20,8 +58,29 @@ pub enum DriverSignal<'a> {
     NodePicked(&'a Tree),
     LeafPicked(&'a Tree),
     LeafUnpicked(&'a Tree),
-    DeadEnd(String),
-    Popped
+    /// Input buffer matches a chord which is the prefix of others
+    Pending(&'a Tree),
+    /// No chord of the node starts with the keys typed in it
+    DeadEnd(&'a Tree, String),
+    Popped,
+    SearchChanged,
+    /// A leaf with placeholders was picked, they are asked for before picking it
+    Prompting(&'a Tree),
+    PromptChanged,
+    /// A leaf with `.confirm` was picked, it waits for the user to confirm it
+    Confirming(&'a Tree),
+    /// The user declined to pick a leaf waiting for confirmation
+    Declined(&'a Tree),
+    Undone,
+    Redone,
+}
+
+
+/// State of a `Driver` which can be undone
+#[derive(Clone)]
+struct Snapshot<'a> {
+    selections: Vec<&'a Tree>,
+    path: Vec<&'a Tree>,
 }
 
 
@@ -38,6 +97,24 @@ pub struct Driver<'a> {
     path: Vec<&'a Tree>,
 
     input_buffer: String,
+
+    /// Stores the ongoing search, if any
+    search: Option<SearchState>,
+
+    /// Stores the placeholders being asked for, if any
+    prompt: Option<PromptState<'a>>,
+
+    /// Answers given to the placeholders of picked leaves, most recent last
+    answers: Vec<(&'a Tree, Vec<String>)>,
+
+    /// Stores the leaf waiting for confirmation, if any
+    confirmation: Option<&'a Tree>,
+
+    /// States before every change, most recent last
+    history: Vec<Snapshot<'a>>,
+
+    /// States reverted by undo, most recently undone last
+    undone: Vec<Snapshot<'a>>,
 }
 
 
@@ -46,11 +123,17 @@ impl<'a> Driver<'a> {
     /// Returns new Driver
     pub fn new(root: &'a Tree, flags: Vec<DriverFlag>) -> Self {
         Self {
-            root: root,
-            flags: flags,
+            root,
+            flags,
             input_buffer: String::new(),
             path: Vec::new(),
-            selections: Vec::new()
+            selections: Vec::new(),
+            search: None,
+            prompt: None,
+            answers: Vec::new(),
+            confirmation: None,
+            history: Vec::new(),
+            undone: Vec::new(),
         }
     }
 
@@ -58,6 +141,14 @@ impl<'a> Driver<'a> {
         Self::new(root, Vec::new())
     }
 
+    pub fn flags(&self) -> &Vec<DriverFlag> {
+        &self.flags
+    }
+
+    pub fn selections<'b>(&'b self) -> &'b Vec<&'a Tree> {
+        &self.selections
+    }
+
     pub fn path<'b>(&'b self) -> &'b Vec<&'a Tree> {
         &self.path
     }
@@ -68,27 +159,257 @@ impl<'a> Driver<'a> {
 
     /// Returns reference to root
     pub fn root(&self) -> &'a Tree {
-        &self.root
+        self.root
     }
 
     /// Gets last selected node or returns root
     pub fn head(&self) -> &'a Tree {
-        *self.path.last().unwrap_or(&&self.root)
+        self.path.last().unwrap_or(&self.root)
     }
 
     pub fn get_transitions(&self) -> Vec<&'a Tree> {
         self.head()
             .transitions_by_prefix(self.input_buffer.as_str())
-            .iter()
-            .map(|(key, value)| *value)
+            .into_values()
             .collect()
     }
 
-    /// Receives a command which changes the driver's current state
-    pub fn drive<'b>(&mut self, command: DriverCommand<'b>) -> DriverSignal<'a> {
+    pub fn search(&self) -> Option<&SearchState> {
+        self.search.as_ref()
+    }
+
+    /// Returns leaves and nodes below head matching the current search query,
+    /// best matches first. Returns nothing if there's no ongoing search.
+    pub fn search_results(&self) -> Vec<SearchResult<'a>> {
+        match &self.search {
+            Some(state) => search::search(self.head(), state.query.as_str()),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn prompt(&self) -> Option<&PromptState<'a>> {
+        self.prompt.as_ref()
+    }
+
+    pub fn confirmation(&self) -> Option<&'a Tree> {
+        self.confirmation
+    }
+
+    /// Returns the value of a picked tree with its placeholders filled in
+    /// by the last answers given for them
+    pub fn value(&self, tree: &Tree) -> String {
+        let answers = self.answers.iter()
+            .rev()
+            .find(|(picked, _)| std::ptr::eq(*picked, tree))
+            .map(|(_, answers)| answers.as_slice())
+            .unwrap_or_default();
+        placeholder::substitute(&tree.data().value, answers)
+    }
+
+    /// Returns the tree whose chord matches the input buffer while waiting
+    /// for the keys of a longer chord. Only happens with `DriverFlag::ChordTimeout`.
+    pub fn pending(&self) -> Option<&'a Tree> {
+        if self.input_buffer.is_empty() || !self.flags.contains(&DriverFlag::ChordTimeout) {
+            return None;
+        }
+        self.head().transition(self.input_buffer.as_str())
+    }
+
+    /// Checks whether typing `input` after the input buffer leads
+    /// to a transition from head
+    pub fn is_bound(&self, input: &str) -> bool {
+        let typed = format!("{}{}", self.input_buffer, input);
+        !self.head().transitions_by_prefix(&typed).is_empty()
+    }
+
+    /// Receives a command which changes the driver's current state.
+    /// Returns every signal produced by the command, in order. eg. a multi
+    /// character transition returns one signal per character.
+    /// Changes to the path or selections are recorded so they can be undone
+    /// as a whole.
+    pub fn drive<'b>(&mut self, command: DriverCommand<'b>) -> Vec<DriverSignal<'a>> {
+        let snapshot = self.snapshot();
+        let signals = self.execute(command);
+        let changed = self.path.len() != snapshot.path.len()
+            || signals.iter().any(|signal| matches!(signal,
+                DriverSignal::NodePicked(_) | DriverSignal::LeafPicked(_)
+                | DriverSignal::LeafUnpicked(_) | DriverSignal::Popped));
+        let restored = signals.iter().any(|signal| matches!(signal,
+                DriverSignal::Undone | DriverSignal::Redone));
+        if changed && !restored {
+            self.history.push(snapshot);
+            self.undone.clear();
+        }
+        signals
+    }
+
+    fn execute<'b>(&mut self, command: DriverCommand<'b>) -> Vec<DriverSignal<'a>> {
         match command {
-            DriverCommand::Backtrack => self.backtrack(),
             DriverCommand::Transition(input) => self.transition(input),
+            DriverCommand::PickResult => self.pick_result(),
+            DriverCommand::Backtrack => vec![self.backtrack()],
+            DriverCommand::Root => vec![self.up(self.path.len())],
+            DriverCommand::Up(levels) => vec![self.up(levels)],
+            DriverCommand::Resolve => vec![self.resolve()],
+            DriverCommand::Search(query) => vec![self.update_search(query)],
+            DriverCommand::SelectResult(index) => vec![self.select_result(index)],
+            DriverCommand::CancelSearch => vec![self.cancel_search()],
+            DriverCommand::Undo => vec![self.undo()],
+            DriverCommand::Redo => vec![self.redo()],
+            DriverCommand::Answer(input) => vec![self.answer(input)],
+            DriverCommand::SubmitAnswer => vec![self.submit_answer()],
+            DriverCommand::CancelPrompt => vec![self.cancel_prompt()],
+            DriverCommand::Confirm => vec![self.confirm()],
+            DriverCommand::Decline => vec![self.decline()],
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot<'a> {
+        Snapshot {
+            selections: self.selections.clone(),
+            path: self.path.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot<'a>) {
+        self.input_buffer.clear();
+        self.search = None;
+        self.prompt = None;
+        self.confirmation = None;
+        self.selections = snapshot.selections;
+        self.path = snapshot.path;
+    }
+
+    fn undo(&mut self) -> DriverSignal<'a> {
+        match self.history.pop() {
+            Some(snapshot) => {
+                self.undone.push(self.snapshot());
+                self.restore(snapshot);
+                DriverSignal::Undone
+            },
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    fn redo(&mut self) -> DriverSignal<'a> {
+        match self.undone.pop() {
+            Some(snapshot) => {
+                self.history.push(self.snapshot());
+                self.restore(snapshot);
+                DriverSignal::Redone
+            },
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    /// Replaces the search query, resetting the selection to the best match
+    fn update_search(&mut self, query: &str) -> DriverSignal<'a> {
+        self.input_buffer.clear();
+        self.search = Some(SearchState { query: String::from(query), selected: 0 });
+        DriverSignal::SearchChanged
+    }
+
+    fn select_result(&mut self, index: usize) -> DriverSignal<'a> {
+        let count = self.search_results().len();
+        match &mut self.search {
+            Some(state) if index < count => {
+                state.selected = index;
+                DriverSignal::SearchChanged
+            },
+            _ => DriverSignal::NoOp,
+        }
+    }
+
+    /// Ends the search and walks the path to the selected result
+    /// as if its chords had been typed.
+    fn pick_result(&mut self) -> Vec<DriverSignal<'a>> {
+        let selected = match &self.search {
+            Some(state) => state.selected,
+            None => return vec![DriverSignal::NoOp],
+        };
+        let mut results = self.search_results();
+        if selected >= results.len() {
+            return vec![DriverSignal::NoOp];
+        }
+        self.search = None;
+        results.swap_remove(selected).path
+            .into_iter()
+            .map(|tree| self.handle_pick(tree))
+            .collect()
+    }
+
+    fn cancel_search(&mut self) -> DriverSignal<'a> {
+        match self.search.take() {
+            Some(_) => DriverSignal::SearchChanged,
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    fn answer(&mut self, input: &str) -> DriverSignal<'a> {
+        match &mut self.prompt {
+            Some(state) => {
+                state.input = String::from(input);
+                DriverSignal::PromptChanged
+            },
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    /// Moves on to the next placeholder, picking the leaf after the last one
+    fn submit_answer(&mut self) -> DriverSignal<'a> {
+        let state = match &mut self.prompt {
+            Some(state) => state,
+            None => return DriverSignal::NoOp,
+        };
+        let input = std::mem::take(&mut state.input);
+        let answer = match (input.is_empty(), &state.current().default) {
+            (true, Some(default)) => default.clone(),
+            _ => input,
+        };
+        state.answers.push(answer);
+        if state.answers.len() < state.placeholders.len() {
+            return DriverSignal::PromptChanged;
+        }
+        // the prompt is over
+        let state = self.prompt.take().unwrap();
+        self.answers.push((state.leaf, state.answers));
+        self.pick_leaf(state.leaf)
+    }
+
+    fn cancel_prompt(&mut self) -> DriverSignal<'a> {
+        match self.prompt.take() {
+            Some(_) => DriverSignal::PromptChanged,
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    fn confirm(&mut self) -> DriverSignal<'a> {
+        match self.confirmation.take() {
+            Some(tree) => {
+                self.selections.push(tree);
+                DriverSignal::LeafPicked(tree)
+            },
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    fn decline(&mut self) -> DriverSignal<'a> {
+        match self.confirmation.take() {
+            Some(tree) => DriverSignal::Declined(tree),
+            None => DriverSignal::NoOp,
+        }
+    }
+
+    /// Picks a leaf whose placeholders have been filled in, unless it
+    /// has to be confirmed first
+    fn pick_leaf(&mut self, tree: &'a Tree) -> DriverSignal<'a> {
+        if tree.data().confirm.is_some() {
+            self.confirmation = Some(tree);
+            DriverSignal::Confirming(tree)
+        }
+        else {
+            self.selections.push(tree);
+            DriverSignal::LeafPicked(tree)
         }
     }
 
@@ -96,25 +417,61 @@ impl<'a> Driver<'a> {
     fn backtrack(&mut self) -> DriverSignal<'a> {
         self.input_buffer.clear();
         match self.path.pop() {
-            Some(tree) => DriverSignal::Popped,
+            Some(_) => DriverSignal::Popped,
             None => DriverSignal::NoOp,
         }
     }
 
-    fn transition<'b>(&mut self, input: &'b str) -> DriverSignal<'a> {
-        let mut result = DriverSignal::NoOp;
-        for c in String::from(input).chars() { //couldn't iterate over slice for some reason
-            result = self.evaluate_char(c);
+    /// Walks up `levels` levels in the tree and clears input buffer
+    fn up(&mut self, levels: usize) -> DriverSignal<'a> {
+        self.input_buffer.clear();
+        let depth = self.path.len().saturating_sub(levels);
+        if depth == self.path.len() {
+            DriverSignal::NoOp
         }
-        result
-        // only return the last transition? feels wrong.
+        else {
+            self.path.truncate(depth);
+            DriverSignal::Popped
+        }
+    }
+
+    /// Input is in chord notation and is evaluated one key at a time
+    fn transition<'b>(&mut self, input: &'b str) -> Vec<DriverSignal<'a>> {
+        chord::parse_lossy(input)
+            .into_iter()
+            .flat_map(|key| self.evaluate_key(key))
+            .collect()
     }
 
-    fn evaluate_char(&mut self, c: char) -> DriverSignal<'a> {
-        self.input_buffer.push(c);
+    /// Evaluates a key typed after the input buffer. Usually produces a single
+    /// signal, unless a pending chord has to be picked before evaluating the key.
+    fn evaluate_key(&mut self, key: KeyToken) -> Vec<DriverSignal<'a>> {
+        let pending = self.pending();
+        self.input_buffer.push_str(&key.to_string());
+        let candidates = self.head().transitions_by_prefix(self.input_buffer.as_str()).len();
         match self.head().transition(self.input_buffer.as_str()) {
-            Option::Some(tree) => self.handle_pick(tree),
-            Option::None => self.handle_incomplete_transition()
+            Option::Some(tree) if candidates > 1 && self.flags.contains(&DriverFlag::ChordTimeout) => {
+                vec![DriverSignal::Pending(tree)]
+            },
+            Option::Some(tree) => vec![self.handle_pick(tree)],
+            Option::None => match pending {
+                // key doesn't continue any longer chord, so the pending one
+                // is picked and the key is evaluated from there
+                Some(tree) if candidates == 0 => {
+                    let mut signals = vec![self.handle_pick(tree)];
+                    signals.extend(self.evaluate_key(key));
+                    signals
+                },
+                _ => vec![self.handle_incomplete_transition(key)]
+            }
+        }
+    }
+
+    /// Picks the pending chord, if any
+    fn resolve(&mut self) -> DriverSignal<'a> {
+        match self.pending() {
+            Some(tree) => self.handle_pick(tree),
+            None => DriverSignal::NoOp,
         }
     }
 
@@ -131,27 +488,39 @@ impl<'a> Driver<'a> {
         // FIXME legacy code. add toggle behavior to leaf
         else if self.toggle() && self.selections.contains(&tree) {
             self.selections = self.selections.iter()
-                .map(|t| *t)
+                .copied()
                 .filter(|t| *t != tree)
                 .collect::<Vec<_>>();
             DriverSignal::LeafUnpicked(tree)
         }
+        else if !placeholder::placeholders(&tree.data().value).is_empty() {
+            self.prompt = Some(PromptState::new(tree));
+            DriverSignal::Prompting(tree)
+        }
         else {
-            self.selections.push(tree);
-            DriverSignal::LeafPicked(tree)
+            self.pick_leaf(tree)
         }
     }
 
-    /// Handle a partial transition
-    fn handle_incomplete_transition(&mut self) -> DriverSignal<'a> {
-        if self.root.transitions_by_prefix(self.input_buffer.as_str()).len() == 0 {
-            let signal = DriverSignal::DeadEnd(String::from(self.input_buffer.as_str()));
-            self.input_buffer.clear();
-            signal
+    /// Handle a partial transition.
+    /// The input buffer is a dead end if no chord from head starts with it,
+    /// `key` is the last key pushed to the buffer.
+    fn handle_incomplete_transition(&mut self, key: KeyToken) -> DriverSignal<'a> {
+        if !self.head().transitions_by_prefix(self.input_buffer.as_str()).is_empty() {
+            return DriverSignal::NoOp;
+        }
+        let signal = DriverSignal::DeadEnd(self.head(), String::from(self.input_buffer.as_str()));
+        if self.flags.contains(&DriverFlag::IgnoreDeadEnd) {
+            let length = self.input_buffer.len() - key.to_string().len();
+            self.input_buffer.truncate(length);
         }
         else {
-            DriverSignal::NoOp
+            self.input_buffer.clear();
+            if self.flags.contains(&DriverFlag::RootOnDeadEnd) {
+                self.path.clear();
+            }
         }
+        signal
     }
 
 
@@ -173,14 +542,16 @@ mod tests {
             name: String::from("leaf"),
             desc: String::from("leaf"),
             chord: String::from("l"),
-            value: String::from("leaf")
+            value: String::from("leaf"),
+            ..Default::default()
         };
         let leaf = Tree::Leaf(leaf_data);
         let n1_data = LeafData {
             name: String::from("n1"),
             desc: String::from("n1"),
             chord: String::from("n1"),
-            value: String::from("n1")
+            value: String::from("n1"),
+            ..Default::default()
         };
         let n1 = Tree::Node(n1_data, vec![leaf]);
 
@@ -188,7 +559,8 @@ mod tests {
             name: String::from("n2"),
             desc: String::from("n2"),
             chord: String::from("n2"),
-            value: String::from("n2")
+            value: String::from("n2"),
+            ..Default::default()
         };
         let n2 = Tree::Node(n2_data, Vec::new());
 
@@ -196,11 +568,11 @@ mod tests {
             name: String::from("root"),
             desc: String::from("root"),
             chord: String::from(""),
-            value: String::from("")
+            value: String::from(""),
+            ..Default::default()
         };
 
-        let root = Tree::Node(root_data, vec![n1, n2]);
-        return root;
+        Tree::Node(root_data, vec![n1, n2])
     }
 
     // TODO add more test cases
@@ -213,12 +585,229 @@ mod tests {
         let n1 = root.children()[&"n1"];
         let leaf = n1.children()[&"l"];
         let mut driver = Driver::default(&tree);
-        assert_eq!(driver.drive(DriverCommand::Backtrack), DriverSignal::NoOp);
-        assert_eq!(driver.drive(DriverCommand::Transition("n")), DriverSignal::NoOp);
-        assert_eq!(driver.drive(DriverCommand::Transition("1")), DriverSignal::NodePicked(n1));
-        assert_eq!(driver.drive(DriverCommand::Transition("l")), DriverSignal::LeafPicked(leaf));
-        assert_eq!(driver.drive(DriverCommand::Transition("k")), DriverSignal::DeadEnd(String::from("k")));
-        assert_eq!(driver.drive(DriverCommand::Backtrack), DriverSignal::Popped);
+        assert_eq!(driver.drive(DriverCommand::Backtrack), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::Transition("n")), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::Transition("1")), vec![DriverSignal::NodePicked(n1)]);
+        assert_eq!(driver.drive(DriverCommand::Transition("l")), vec![DriverSignal::LeafPicked(leaf)]);
+        assert_eq!(driver.drive(DriverCommand::Transition("k")), vec![DriverSignal::DeadEnd(n1, String::from("k"))]);
+        assert_eq!(driver.drive(DriverCommand::Backtrack), vec![DriverSignal::Popped]);
+    }
+
+    #[test]
+    fn test_multi_key_transition_returns_every_signal() {
+        let tree = build_tree();
+        let n1 = tree.children()[&"n1"];
+        let leaf = n1.children()[&"l"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Transition("")), vec![]);
+        assert_eq!(driver.drive(DriverCommand::Transition("n1l")),
+                   vec![DriverSignal::NoOp, DriverSignal::NodePicked(n1), DriverSignal::LeafPicked(leaf)]);
+        // the whole transition is undone at once
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::Undone]);
+        assert!(driver.path().is_empty());
+        assert!(driver.selections().is_empty());
+    }
+
+    #[test]
+    fn test_dead_ends_are_relative_to_head() {
+        let tree = build_tree();
+        let n1 = tree.children()[&"n1"];
+        let mut driver = Driver::default(&tree);
+        driver.drive(DriverCommand::Transition("n1"));
+        // "n" is a prefix at root but not in n1
+        assert_eq!(driver.drive(DriverCommand::Transition("n")), vec![DriverSignal::DeadEnd(n1, String::from("n"))]);
+        assert_eq!(driver.input_buffer(), "");
+        assert_eq!(driver.path(), &vec![n1]);
+
+        let mut driver = Driver::new(&tree, vec![DriverFlag::IgnoreDeadEnd]);
+        driver.drive(DriverCommand::Transition("n"));
+        assert_eq!(driver.drive(DriverCommand::Transition("x")), vec![DriverSignal::DeadEnd(&tree, String::from("nx"))]);
+        assert_eq!(driver.input_buffer(), "n");
+
+        let mut driver = Driver::new(&tree, vec![DriverFlag::RootOnDeadEnd]);
+        driver.drive(DriverCommand::Transition("n1"));
+        assert_eq!(driver.drive(DriverCommand::Transition("x")), vec![DriverSignal::DeadEnd(n1, String::from("x"))]);
+        assert!(driver.path().is_empty());
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::Undone]);
+        assert_eq!(driver.path(), &vec![n1]);
+    }
+
+    #[test]
+    fn test_special_keys_are_matched_by_key() {
+        let leaf_data = LeafData {
+            name: String::from("grep"),
+            desc: String::from("grep"),
+            chord: String::from("<c-G>s"),
+            value: String::from("grep"),
+            ..Default::default()
+        };
+        let root_data = LeafData {
+            name: String::from("root"),
+            desc: String::from("root"),
+            chord: String::from(""),
+            value: String::from(""),
+            ..Default::default()
+        };
+        let tree = Tree::Node(root_data, vec![Tree::Leaf(leaf_data)]);
+        let leaf = tree.children()[&"<c-G>s"];
+        let mut driver = Driver::default(&tree);
+        assert!(driver.is_bound("<C-g>"));
+        assert!(!driver.is_bound("<"));
+        assert_eq!(driver.drive(DriverCommand::Transition("<C-g>")), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.input_buffer(), "<C-g>");
+        assert_eq!(driver.drive(DriverCommand::Transition("s")), vec![DriverSignal::LeafPicked(leaf)]);
+    }
+
+    #[test]
+    fn test_chord_timeout_waits_for_longer_chords() {
+        let data = |name: &str, chord: &str| LeafData {
+            name: String::from(name),
+            desc: String::from(name),
+            chord: String::from(chord),
+            value: String::from(name),
+            ..Default::default()
+        };
+        let tree = Tree::Node(data("root", ""), vec![
+            Tree::Leaf(data("g", "g")),
+            Tree::Leaf(data("gg", "gg")),
+            Tree::Leaf(data("x", "x")),
+        ]);
+        let g = tree.children()[&"g"];
+        let gg = tree.children()[&"gg"];
+        let x = tree.children()[&"x"];
+        let mut driver = Driver::new(&tree, vec![DriverFlag::ChordTimeout]);
+        assert_eq!(driver.drive(DriverCommand::Resolve), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::Transition("g")), vec![DriverSignal::Pending(g)]);
+        assert_eq!(driver.pending(), Some(g));
+        assert_eq!(driver.drive(DriverCommand::Transition("g")), vec![DriverSignal::LeafPicked(gg)]);
+        driver.drive(DriverCommand::Transition("g"));
+        assert_eq!(driver.drive(DriverCommand::Resolve), vec![DriverSignal::LeafPicked(g)]);
+        // a key which doesn't continue the pending chord picks it first
+        driver.drive(DriverCommand::Transition("g"));
+        assert_eq!(driver.drive(DriverCommand::Transition("x")),
+                   vec![DriverSignal::LeafPicked(g), DriverSignal::LeafPicked(x)]);
+        assert_eq!(driver.selections(), &vec![gg, g, g, x]);
+
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Transition("g")), vec![DriverSignal::LeafPicked(g)]);
+    }
+
+    #[test]
+    fn test_root_and_up() {
+        let tree = build_tree();
+        let n1 = tree.children()[&"n1"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Root), vec![DriverSignal::NoOp]);
+        driver.drive(DriverCommand::Transition("n1"));
+        driver.drive(DriverCommand::Transition("x"));
+        assert_eq!(driver.drive(DriverCommand::Up(0)), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.path(), &vec![n1]);
+        assert_eq!(driver.drive(DriverCommand::Up(5)), vec![DriverSignal::Popped]);
+        assert!(driver.path().is_empty());
+
+        driver.drive(DriverCommand::Transition("n1"));
+        driver.drive(DriverCommand::Transition("n"));
+        assert_eq!(driver.drive(DriverCommand::Root), vec![DriverSignal::Popped]);
+        assert!(driver.path().is_empty());
+        assert_eq!(driver.input_buffer(), "");
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let tree = build_tree();
+        let n1 = tree.children()[&"n1"];
+        let leaf = n1.children()[&"l"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::NoOp]);
+        driver.drive(DriverCommand::Transition("n1"));
+        driver.drive(DriverCommand::Transition("l"));
+        assert_eq!(driver.selections(), &vec![n1, leaf]);
+
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::Undone]);
+        assert_eq!(driver.selections(), &vec![n1]);
+        assert_eq!(driver.path(), &vec![n1]);
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::Undone]);
+        assert!(driver.selections().is_empty());
+        assert!(driver.path().is_empty());
+
+        assert_eq!(driver.drive(DriverCommand::Redo), vec![DriverSignal::Redone]);
+        assert_eq!(driver.path(), &vec![n1]);
+        // a new change discards the undone states
+        driver.drive(DriverCommand::Backtrack);
+        assert_eq!(driver.drive(DriverCommand::Redo), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::Undone]);
+        assert_eq!(driver.path(), &vec![n1]);
+    }
+
+    #[test]
+    fn test_search_walks_to_result() {
+        let tree = build_tree();
+        let n1 = tree.children()[&"n1"];
+        let leaf = n1.children()[&"l"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::PickResult), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::Search("lf")), vec![DriverSignal::SearchChanged]);
+        assert_eq!(driver.search_results().len(), 1);
+        assert_eq!(driver.drive(DriverCommand::SelectResult(1)), vec![DriverSignal::NoOp]);
+        assert_eq!(driver.drive(DriverCommand::PickResult),
+                   vec![DriverSignal::NodePicked(n1), DriverSignal::LeafPicked(leaf)]);
+        assert_eq!(driver.search(), None);
+        assert_eq!(driver.path(), &vec![n1]);
+
+        // picking a node enters it
+        let mut driver = Driver::default(&tree);
+        driver.drive(DriverCommand::Search("n1"));
+        assert_eq!(driver.drive(DriverCommand::PickResult), vec![DriverSignal::NodePicked(n1)]);
+        assert_eq!(driver.path(), &vec![n1]);
+    }
+
+    #[test]
+    fn test_placeholders_are_asked_before_picking() {
+        let leaf = Tree::Leaf(LeafData {
+            name: String::from("checkout"),
+            chord: String::from("c"),
+            value: String::from("git checkout {?branch:main} {?file}"),
+            ..Default::default()
+        });
+        let tree = Tree::Node(LeafData::default(), vec![leaf]);
+        let leaf = tree.children()[&"c"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Transition("c")), vec![DriverSignal::Prompting(leaf)]);
+        assert!(driver.selections().is_empty());
+        assert_eq!(driver.prompt().unwrap().current().name, "branch");
+        assert_eq!(driver.drive(DriverCommand::SubmitAnswer), vec![DriverSignal::PromptChanged]);
+        assert_eq!(driver.drive(DriverCommand::Answer("README")), vec![DriverSignal::PromptChanged]);
+        assert_eq!(driver.drive(DriverCommand::SubmitAnswer), vec![DriverSignal::LeafPicked(leaf)]);
+        assert_eq!(driver.prompt(), None);
+        assert_eq!(driver.value(leaf), "git checkout main README");
+
+        driver.drive(DriverCommand::Transition("c"));
+        assert_eq!(driver.drive(DriverCommand::CancelPrompt), vec![DriverSignal::PromptChanged]);
+        assert_eq!(driver.selections(), &vec![leaf]);
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let leaf = Tree::Leaf(LeafData {
+            name: String::from("reset"),
+            chord: String::from("r"),
+            value: String::from("git reset --hard"),
+            confirm: Some(String::from("Discard changes?")),
+            ..Default::default()
+        });
+        let tree = Tree::Node(LeafData::default(), vec![leaf]);
+        let leaf = tree.children()[&"r"];
+        let mut driver = Driver::default(&tree);
+        assert_eq!(driver.drive(DriverCommand::Transition("r")), vec![DriverSignal::Confirming(leaf)]);
+        assert_eq!(driver.confirmation(), Some(leaf));
+        assert_eq!(driver.drive(DriverCommand::Decline), vec![DriverSignal::Declined(leaf)]);
+        assert!(driver.selections().is_empty());
+        assert_eq!(driver.drive(DriverCommand::Undo), vec![DriverSignal::NoOp]);
+
+        driver.drive(DriverCommand::Transition("r"));
+        assert_eq!(driver.drive(DriverCommand::Confirm), vec![DriverSignal::LeafPicked(leaf)]);
+        assert_eq!(driver.confirmation(), None);
+        assert_eq!(driver.selections(), &vec![leaf]);
     }
 
 }
@@ -0,0 +1,100 @@
+use std::io::Result;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus};
+
+use super::tree::LeafData;
+
+/// Shell used when `$SHELL` isn't set
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+
+/// Runs the value of picked leaves as shell commands
+pub struct Executor {
+    shell: String,
+}
+
+impl Executor {
+
+    pub fn new(shell: &str) -> Self {
+        Self { shell: String::from(shell) }
+    }
+
+    /// Returns an Executor using the user's `$SHELL`
+    pub fn from_env() -> Self {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| String::from(DEFAULT_SHELL));
+        Self::new(shell.as_str())
+    }
+
+    /// Builds the command which runs `value` with the shell, in the working
+    /// directory and environment of the leaf it comes from
+    pub fn command(&self, data: &LeafData, value: &str) -> Command {
+        let mut command = Command::new(self.shell.as_str());
+        command.arg("-c").arg(value);
+        if let Some(cwd) = &data.cwd {
+            command.current_dir(expand_home(cwd));
+        }
+        command.envs(data.env.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+        command
+    }
+
+    /// Runs the value of a leaf, with its placeholders filled in, and waits
+    /// for it to finish. The command inherits stdin, stdout and stderr.
+    pub fn run(&self, data: &LeafData, value: &str) -> Result<ExitStatus> {
+        let mut child = self.command(data, value).spawn()?;
+        // Ctrl-C while the command runs should stop the command, not pickem.
+        // The handler is only replaced after spawning so the command keeps the default one.
+        let previous = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+        let status = child.wait();
+        unsafe { libc::signal(libc::SIGINT, previous) };
+        status
+    }
+}
+
+
+/// Returns the code to exit with after a command finished with `status`.
+/// Like shells, a command killed by a signal gives 128 plus the signal number.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    status.code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+
+/// Replaces a leading `~` in a path with the user's home directory
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => String::from(path),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_uses_cwd_and_env() {
+        let data = LeafData {
+            value: String::from("test \"$PICKEM_TEST\" = value && test \"$(pwd)\" = /"),
+            cwd: Some(String::from("/")),
+            env: vec![(String::from("PICKEM_TEST"), String::from("value"))],
+            ..Default::default()
+        };
+        let executor = Executor::new("/bin/sh");
+        assert!(executor.run(&data, &data.value).unwrap().success());
+
+        let data = LeafData { value: String::from("exit 3"), ..Default::default() };
+        assert_eq!(executor.run(&data, &data.value).unwrap().code(), Some(3));
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let executor = Executor::new("/bin/sh");
+        let data = LeafData { value: String::from("exit 3"), ..Default::default() };
+        assert_eq!(exit_code(executor.run(&data, &data.value).unwrap()), 3);
+
+        let data = LeafData { value: String::from("kill -TERM $$"), ..Default::default() };
+        assert_eq!(exit_code(executor.run(&data, &data.value).unwrap()), 128 + libc::SIGTERM);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::tree::Tree;
+use crate::chord;
+
+
+/// Renders `tree` as a Graphviz digraph.
+/// Edges are labeled with the chord of the child they lead to.
+/// `Node`s are drawn as boxes and leaves as ellipses; nodes whose children
+/// were cut off by `max_depth` are drawn with a dashed border.
+pub fn to_dot(tree: &Tree, max_depth: Option<usize>) -> String {
+    let mut doc = String::from("digraph pickem {\n");
+    let mut next_id = 0;
+    write_tree(&mut doc, tree, &mut next_id, 0, max_depth);
+    doc.push_str("}\n");
+    doc
+}
+
+/// Writes the statement for `tree` and its edges, returning `tree`'s id
+fn write_tree(doc: &mut String, tree: &Tree, next_id: &mut usize, depth: usize, max_depth: Option<usize>) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let name = escape(&tree.data().name);
+    match tree {
+        Tree::Leaf(_) => {
+            doc.push_str(&format!("    n{} [label=\"{}\", shape=ellipse];\n", id, name));
+        },
+        Tree::Node(_, children) => {
+            let truncated = max_depth.is_some_and(|max| depth >= max);
+            let style = if truncated { "dashed" } else { "solid" };
+            doc.push_str(&format!("    n{} [label=\"{}\", shape=box, style={}];\n", id, name, style));
+            if !truncated {
+                for child in children {
+                    let child_id = write_tree(doc, child, next_id, depth + 1, max_depth);
+                    doc.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n",
+                                          id, child_id, escape(&chord::readable(&child.data().chord))));
+                }
+            }
+        }
+    }
+    id
+}
+
+/// Escapes characters which would end a DOT string early
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn tree() -> Tree {
+        let yml = "
+git:
+  .chord: g
+  status:
+    .chord: s
+quote:
+  .chord: q
+  .value: echo \"hi\"
+";
+        parser::parse(yml).0
+    }
+
+    #[test]
+    fn test_to_dot_labels_edges_with_chords() {
+        let expected = "digraph pickem {
+    n0 [label=\"root\", shape=box, style=solid];
+    n1 [label=\"git\", shape=box, style=solid];
+    n2 [label=\"status\", shape=ellipse];
+    n1 -> n2 [label=\"s\"];
+    n0 -> n1 [label=\"g\"];
+    n3 [label=\"quote\", shape=ellipse];
+    n0 -> n3 [label=\"q\"];
+}
+";
+        assert_eq!(to_dot(&tree(), None), expected);
+    }
+
+    #[test]
+    fn test_to_dot_stops_at_max_depth() {
+        let doc = to_dot(&tree(), Some(1));
+        assert!(doc.contains("n1 [label=\"git\", shape=box, style=dashed];"));
+        assert!(!doc.contains("status"));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::tree::Tree;
+use super::chord_sequence;
+
+const TITLE: &str = "Pickem cheat sheet";
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+kbd { color: #c00; font-weight: bold; }
+section section { margin-left: 1.5em; }";
+
+
+/// Renders `tree` as a standalone HTML cheat sheet.
+/// The document mirrors the Markdown export: one section per node
+/// and a table with the leaves of every node.
+pub fn to_html(tree: &Tree, max_depth: Option<usize>) -> String {
+    let mut body = String::new();
+    write_section(&mut body, tree, &[], 1, max_depth);
+    format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+{style}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}</body>
+</html>
+", title = TITLE, style = STYLE, body = body)
+}
+
+fn write_section(doc: &mut String, tree: &Tree, chords: &[&str], depth: usize, max_depth: Option<usize>) {
+    let children = match tree {
+        Tree::Node(_, children) => children,
+        Tree::Leaf(_) => return,
+    };
+    let data = tree.data();
+    if depth > 1 {
+        let level = depth.min(6);
+        doc.push_str("<section>\n");
+        doc.push_str(&format!("<h{level}>{} <kbd>{}</kbd></h{level}>\n",
+                              escape(&data.name),
+                              escape(&chord_sequence(chords)),
+                              level = level));
+        if data.desc != data.name {
+            doc.push_str(&format!("<p>{}</p>\n", escape(&data.desc)));
+        }
+    }
+
+    // `depth` starts at 1 for root, so children of this node are `depth` levels below root
+    if max_depth.is_some_and(|max| depth > max) {
+        close_section(doc, depth);
+        return;
+    }
+
+    let leaves = children.iter().filter(|child| child.is_leaf()).collect::<Vec<_>>();
+    if !leaves.is_empty() {
+        doc.push_str("<table>\n<tr><th>Chord</th><th>Name</th><th>Description</th><th>Value</th></tr>\n");
+        for leaf in leaves {
+            let data = leaf.data();
+            let mut leaf_chords = chords.to_vec();
+            leaf_chords.push(data.chord.as_str());
+            doc.push_str(&format!("<tr><td><kbd>{}</kbd></td><td>{}</td><td>{}</td><td><code>{}</code></td></tr>\n",
+                                  escape(&chord_sequence(&leaf_chords)),
+                                  escape(&data.name),
+                                  escape(&data.desc),
+                                  escape(&data.value)));
+        }
+        doc.push_str("</table>\n");
+    }
+
+    for child in children.iter().filter(|child| !child.is_leaf()) {
+        let mut child_chords = chords.to_vec();
+        child_chords.push(child.data().chord.as_str());
+        write_section(doc, child, &child_chords, depth + 1, max_depth);
+    }
+
+    close_section(doc, depth);
+}
+
+fn close_section(doc: &mut String, depth: usize) {
+    if depth > 1 {
+        doc.push_str("</section>\n");
+    }
+}
+
+/// Escapes HTML special characters
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_to_html_escapes_and_nests_sections() {
+        let yml = "
+git:
+  .chord: g
+  log:
+    .chord: l
+    .value: git log > log.txt
+";
+        let (tree, _) = parser::parse(yml);
+        let doc = to_html(&tree, None);
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.contains("<section>\n<h2>git <kbd>g</kbd></h2>\n"));
+        assert!(doc.contains("<tr><td><kbd>g l</kbd></td><td>log</td><td>log</td><td><code>git log &gt; log.txt</code></td></tr>"));
+        assert!(doc.ends_with("</section>\n</body>\n</html>\n"));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::tree::Tree;
+use super::chord_sequence;
+
+const TITLE: &str = "Pickem cheat sheet";
+
+
+/// Renders `tree` as a Markdown cheat sheet.
+/// Every node becomes a section whose heading has the node's chord sequence,
+/// the leaves of a node are listed in a table within its section.
+pub fn to_markdown(tree: &Tree, max_depth: Option<usize>) -> String {
+    let mut doc = format!("# {}\n", TITLE);
+    write_section(&mut doc, tree, &[], 1, max_depth);
+    doc
+}
+
+fn write_section(doc: &mut String, tree: &Tree, chords: &[&str], depth: usize, max_depth: Option<usize>) {
+    let children = match tree {
+        Tree::Node(_, children) => children,
+        Tree::Leaf(_) => return,
+    };
+    let data = tree.data();
+    if depth > 1 {
+        let level = "#".repeat(depth.min(6));
+        doc.push_str(&format!("\n{} {} `{}`\n", level, escape(&data.name), chord_sequence(chords)));
+        if data.desc != data.name {
+            doc.push_str(&format!("\n{}\n", escape(&data.desc)));
+        }
+    }
+
+    // `depth` starts at 1 for root, so children of this node are `depth` levels below root
+    if max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+
+    let leaves = children.iter().filter(|child| child.is_leaf()).collect::<Vec<_>>();
+    if !leaves.is_empty() {
+        doc.push_str("\n| Chord | Name | Description | Value |\n");
+        doc.push_str("|---|---|---|---|\n");
+        for leaf in leaves {
+            let data = leaf.data();
+            let mut leaf_chords = chords.to_vec();
+            leaf_chords.push(data.chord.as_str());
+            doc.push_str(&format!("| `{}` | {} | {} | `{}` |\n",
+                                  chord_sequence(&leaf_chords),
+                                  escape(&data.name),
+                                  escape(&data.desc),
+                                  escape(&data.value)));
+        }
+    }
+
+    for child in children.iter().filter(|child| !child.is_leaf()) {
+        let mut child_chords = chords.to_vec();
+        child_chords.push(child.data().chord.as_str());
+        write_section(doc, child, &child_chords, depth + 1, max_depth);
+    }
+}
+
+/// Escapes characters which would break a table cell
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_to_markdown_groups_leaves_by_node() {
+        let yml = "
+git:
+  .chord: g
+  .desc: git shortcuts
+  status:
+    .chord: s
+    .value: git status
+pipe:
+  .chord: p
+  .value: a | b
+";
+        let (tree, _) = parser::parse(yml);
+        let doc = to_markdown(&tree, None);
+        let expected = "# Pickem cheat sheet
+
+| Chord | Name | Description | Value |
+|---|---|---|---|
+| `p` | pipe | pipe | `a \\| b` |
+
+## git `g`
+
+git shortcuts
+
+| Chord | Name | Description | Value |
+|---|---|---|---|
+| `g s` | status | status | `git status` |
+";
+        assert_eq!(doc, expected);
+    }
+}
@@ -0,0 +1,53 @@
+pub mod markdown;
+pub mod html;
+pub mod dot;
+
+use std::str::FromStr;
+
+use super::tree::Tree;
+use super::chord;
+
+
+/// Documents a `Tree` can be exported to
+#[derive(PartialEq, Debug, Clone)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Dot
+}
+
+impl ExportFormat {
+    /// Names accepted by `from_str`, used by the CLI
+    pub const NAMES: &'static [&'static str] = &["markdown", "html", "dot"];
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "dot" => Ok(ExportFormat::Dot),
+            _ => Err(format!("{} is not a valid export format", s))
+        }
+    }
+}
+
+/// Renders `tree` as a document of the given format.
+/// `max_depth` limits how many levels below root are rendered.
+pub fn export(tree: &Tree, format: &ExportFormat, max_depth: Option<usize>) -> String {
+    match format {
+        ExportFormat::Markdown => markdown::to_markdown(tree, max_depth),
+        ExportFormat::Html => html::to_html(tree, max_depth),
+        ExportFormat::Dot => dot::to_dot(tree, max_depth),
+    }
+}
+
+/// Joins the chords of a path into the key sequence a user has to type
+pub fn chord_sequence(chords: &[&str]) -> String {
+    chords.iter()
+        .map(|chord| chord::readable(chord))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
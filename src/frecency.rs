@@ -0,0 +1,257 @@
+use std::io::{Result, ErrorKind};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::tree::{Tree, LeafData};
+
+/// Name of the pseudo-node listing the most recent picks
+pub const RECENT_NAME: &str = "recent picks";
+/// Chord of the pseudo-node listing the most recent picks
+pub const RECENT_CHORD: &str = "<Tab>";
+/// Chords of the leaves of the recent picks pseudo-node, most recent first
+const RECENT_CHORDS: &str = "123456789";
+/// Separates the names in the path of a recent pick
+const PATH_SEPARATOR: &str = " > ";
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+
+/// How often and how recently a leaf was picked
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    /// Names of the trees from the root (exclusive) to the leaf (inclusive)
+    path: Vec<String>,
+    count: u64,
+    /// Seconds since the epoch of the last pick
+    last: u64,
+}
+
+impl Entry {
+    /// Picks weigh more the more recent they are
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+        let weight = match age {
+            age if age < HOUR => 4.0,
+            age if age < DAY => 2.0,
+            age if age < WEEK => 1.0,
+            _ => 0.5,
+        };
+        self.count as f64 * weight
+    }
+}
+
+
+/// Picks of the leaves of a menu, stored in a file with a line per leaf:
+/// the number of picks, the time of the last one and the names on the
+/// leaf's path, separated by tabs
+#[derive(Clone, Debug, Default)]
+pub struct Frecency {
+    file: Option<PathBuf>,
+    entries: Vec<Entry>,
+}
+
+impl Frecency {
+
+    /// Returns the file storing the picks of the menu in `menu`, under
+    /// `$XDG_DATA_HOME/pickem`, or `~/.local/share/pickem` if it isn't set.
+    /// The file is named after the menu's file and a hash of its canonical path.
+    pub fn data_file(menu: &Path) -> Option<PathBuf> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+        let menu = fs::canonicalize(menu).ok()?;
+        let stem = menu.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let name = format!("{}-{:016x}", stem, fnv1a(menu.as_os_str().as_bytes()));
+        Some(data_home.join("pickem").join("frecency").join(name))
+    }
+
+    /// Reads the picks stored in `file`. A missing file has no picks.
+    pub fn load(file: &Path) -> Result<Self> {
+        let data = match fs::read_to_string(file) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let entries = data.lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let count = fields.next()?.parse().ok()?;
+                let last = fields.next()?.parse().ok()?;
+                let path = fields.map(String::from).collect::<Vec<_>>();
+                Some(Entry { path, count, last }).filter(|entry| !entry.path.is_empty())
+            })
+            .collect();
+        Ok(Self { file: Some(file.to_path_buf()), entries })
+    }
+
+    /// Writes the picks back to the file they were read from
+    pub fn save(&self) -> Result<()> {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let data = self.entries.iter()
+            .map(|entry| format!("{}\t{}\t{}\n", entry.count, entry.last, entry.path.join("\t")))
+            .collect::<String>();
+        fs::write(file, data)
+    }
+
+    /// Drops the picks of leaves which aren't in `root` anymore
+    pub fn prune(&mut self, root: &Tree) {
+        self.entries.retain(|entry| find(root, &entry.path).is_some_and(Tree::is_leaf));
+    }
+
+    /// Records a pick of the leaf at `path`
+    pub fn record(&mut self, path: Vec<String>) {
+        let now = now();
+        match self.entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => {
+                entry.count += 1;
+                entry.last = now;
+            },
+            None => self.entries.push(Entry { path, count: 1, last: now }),
+        }
+    }
+
+    /// Returns how often and recently the tree at `path` was picked.
+    /// A node scores the picks of every leaf below it.
+    pub fn score(&self, path: &[String]) -> f64 {
+        let now = now();
+        self.entries.iter()
+            .filter(|entry| entry.path.starts_with(path))
+            .map(|entry| entry.score(now))
+            .sum()
+    }
+
+    /// Returns the paths of the last `n` leaves picked, most recent first
+    pub fn recent(&self, n: usize) -> Vec<&[String]> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last));
+        entries.into_iter()
+            .take(n)
+            .map(|entry| entry.path.as_slice())
+            .collect()
+    }
+
+    /// Adds a pseudo-node to the root with copies of the last leaves picked,
+    /// named after their path, which they keep in `origin`. Nothing is added
+    /// if no leaf was picked or if the root already uses its chord.
+    pub fn with_recent(&self, root: Tree) -> Tree {
+        let recent = self.recent(RECENT_CHORDS.len())
+            .into_iter()
+            .zip(RECENT_CHORDS.chars())
+            .filter_map(|(path, chord)| {
+                let data = find(&root, path)?.data();
+                Some(Tree::Leaf(LeafData {
+                    name: path.join(PATH_SEPARATOR),
+                    chord: chord.to_string(),
+                    origin: Some(path.to_vec()),
+                    ..data.clone()
+                }))
+            })
+            .collect::<Vec<_>>();
+        if recent.is_empty() || root.transition(RECENT_CHORD).is_some() {
+            return root;
+        }
+        let node = Tree::Node(LeafData {
+            name: String::from(RECENT_NAME),
+            desc: String::from(RECENT_NAME),
+            chord: String::from(RECENT_CHORD),
+            value: String::from(RECENT_NAME),
+            ..Default::default()
+        }, recent);
+        match root {
+            Tree::Node(data, mut children) => {
+                children.push(node);
+                Tree::Node(data, children)
+            },
+            leaf => leaf,
+        }
+    }
+}
+
+
+/// Returns the tree at the end of `path`, a list of names starting below `root`
+pub fn find<'a>(root: &'a Tree, path: &[String]) -> Option<&'a Tree> {
+    path.iter().try_fold(root, |tree, name| match tree {
+        Tree::Node(_, children) => children.iter().find(|child| &child.data().name == name),
+        Tree::Leaf(_) => None,
+    })
+}
+
+/// Returns the names on the path to `tree`, a child of the last tree in `path`
+pub fn path_names(path: &[&Tree], tree: &Tree) -> Vec<String> {
+    path.iter()
+        .chain(std::iter::once(&tree))
+        .map(|tree| tree.data().name.clone())
+        .collect()
+}
+
+/// 64 bit FNV-1a hash, which unlike the hashers of std is the same in
+/// every build, so the names of the data files don't change
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    #[test]
+    fn test_record_prune_and_recent() {
+        let (tree, _) = parser::parse("git:\n  status: git status\n  log: git log\nls: ls\n");
+        let file = std::env::temp_dir().join(format!("pickem-frecency-{}", std::process::id()));
+        let mut frecency = Frecency::load(&file).unwrap();
+        frecency.record(path(&["git", "status"]));
+        frecency.record(path(&["git", "status"]));
+        frecency.record(path(&["removed"]));
+        frecency.save().unwrap();
+
+        let mut frecency = Frecency::load(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+        frecency.prune(&tree);
+        assert_eq!(frecency.recent(5), vec![path(&["git", "status"]).as_slice()]);
+        assert!(frecency.score(&path(&["git"])) > frecency.score(&path(&["ls"])));
+
+        let tree = frecency.with_recent(tree);
+        let recent = tree.transition(RECENT_CHORD).unwrap();
+        let status = recent.transition("1").unwrap();
+        assert_eq!(status.data().value, "git status");
+        assert_eq!(status.data().name, "git > status");
+        assert_eq!(status.data().origin, Some(path(&["git", "status"])));
+    }
+
+    #[test]
+    fn test_data_files_dont_collide() {
+        let dir = std::env::temp_dir().join(format!("pickem-data-file-{}", std::process::id()));
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::write(dir.join("a%b"), "").unwrap();
+        fs::write(dir.join("a").join("b"), "").unwrap();
+        let first = Frecency::data_file(&dir.join("a%b"));
+        let second = Frecency::data_file(&dir.join("a").join("b"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(first.is_some());
+        assert_ne!(first, second);
+    }
+}
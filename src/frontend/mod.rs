@@ -1,14 +1,59 @@
 pub mod tui;
 
 use std::io::{Result};
-use super::driver::{Driver, DriverSignal, DriverCommand};
+use std::str::FromStr;
+use super::driver::{Driver, DriverSignal};
 
 pub trait View {
     fn update(&mut self, driver: &Driver, signal: &DriverSignal) -> Result<()>;
+
+    /// Called before another program takes over the terminal.
+    /// Views which draw on the terminal should restore it.
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once the terminal is given back after `suspend`
+    fn resume(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the run ends with an error. Views shouldn't output
+    /// what was picked so far then.
+    fn abort(&mut self) {}
+
+    /// Shows a message from the controller to the user, eg. the exit
+    /// status of a command
+    fn notify(&mut self, _driver: &Driver, _message: &str) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub trait Controller {
     /// Entrypoint for controller instance. Method should run until
-    /// pickem execution is complete.
-    fn run(&mut self) -> Result<()>;
+    /// pickem execution is complete. Returns the code pickem exits with.
+    fn run(&mut self) -> Result<i32>;
+}
+
+
+/// How the output view prints what is picked
+pub enum OutputFormat {
+    Value,
+    Signal
+}
+
+impl OutputFormat {
+    pub const NAMES: &'static [&'static str] = &["value", "signal"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "value" => Ok(OutputFormat::Value),
+            "signal" => Ok(OutputFormat::Signal),
+            _ => Err(format!("{} is not a valid output format", s))
+        }
+    }
 }
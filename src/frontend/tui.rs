@@ -1,17 +1,24 @@
-use std::io::{Result, Write, ErrorKind};
+use std::io::{Result, Read, Write, Error};
 use std::fs::{OpenOptions, File};
+use std::os::unix::io::AsRawFd;
+use std::collections::VecDeque;
+use std::time::Duration;
 
 use crate::util;
-use crate::tree::Tree;
 use crate::driver::{Driver, DriverSignal, DriverCommand};
-use crate::frontend::View;
+use crate::search::SearchState;
+use crate::placeholder::PromptState;
+use crate::chord::{self, KeyToken};
+use crate::keys::{Control, Keybindings};
+use crate::tree::Tree;
+use crate::exec::{self, Executor};
+use crate::frecency::{self, Frecency};
+use crate::theme::Theme;
+use crate::frontend::{View, OutputFormat};
 use crate::frontend::Controller as ControllerTrait;
 
 use termion;
-use termion::AsyncReader;
-use termion::input::Keys;
-use termion::input::TermRead;
-use termion::event::Key;
+use termion::event::{self, Event, Key};
 use termios;
 use termios::Termios;
 
@@ -21,6 +28,14 @@ pub enum Flags {
     QuitDeadEnd,
     LoopMode,
     OutputOnPick,
+    /// Milliseconds to wait for the keys of a longer chord before picking a pending one
+    ChordTimeout(u64),
+    /// Run the value of every picked leaf, not only of leaves marked with `.exec`
+    ExecAll,
+    /// Return to the menu after running a command instead of quitting
+    Launcher,
+    /// Shell used to run commands, defaults to `$SHELL`
+    Shell(String),
 }
 
 
@@ -28,6 +43,8 @@ pub struct Controller<'driver, 'tree, 'view> {
     driver: &'driver mut Driver<'tree>,
     views: Vec<&'view mut dyn View>,
     flags: Vec<Flags>,
+    keys: Keybindings,
+    exit_code: i32,
 }
 
 
@@ -35,72 +52,336 @@ impl<'driver, 'tree, 'view> Controller<'driver, 'tree, 'view> {
 
     pub fn new(driver: &'driver mut Driver<'tree>, views: Vec<&'view mut dyn View>, flags: Vec<Flags>) 
         -> Result<Self> {
-        Ok(Self { driver, views, flags })
+        Ok(Self { driver, views, flags, keys: Keybindings::default(), exit_code: 0 })
+    }
+
+    pub fn set_keybindings(&mut self, keys: Keybindings) {
+        self.keys = keys;
     }
 
     /// Handles an user key press. Returns a Result of bool.
     /// If bool is false, the run is over and it should return to main
     fn handle_input(&mut self, key: Key) -> Result<bool> {
+        if self.driver.confirmation().is_some() {
+            // anything but yes declines
+            let command = match key {
+                Key::Char('y') | Key::Char('Y') => DriverCommand::Confirm,
+                _ => DriverCommand::Decline,
+            };
+            let signals = self.driver.drive(command);
+            return self.handle_signals(signals);
+        }
+        if let Some(state) = self.driver.prompt() {
+            let input = state.input.clone();
+            return self.handle_prompt_input(key, input);
+        }
+        if let Some(state) = self.driver.search() {
+            let state = state.clone();
+            return self.handle_search_input(key, state.query, state.selected);
+        }
+        // Keys bound to a chord take precedence over controls
+        let token = key_token(key);
+        if let Some(token) = &token {
+            if self.driver.is_bound(&token.to_string()) {
+                return self.transition(token);
+            }
+        }
+        let command = match self.keys.control(key) {
+            Some(Control::Search) if self.starts_search() => Some(DriverCommand::Search("")),
+            Some(Control::Search) => None,
+            Some(Control::Quit) => return Result::Ok(false),
+            Some(Control::Back) => Some(DriverCommand::Backtrack),
+            Some(Control::Root) => Some(DriverCommand::Root),
+            Some(Control::Undo) => Some(DriverCommand::Undo),
+            Some(Control::Redo) => Some(DriverCommand::Redo),
+            None => None,
+        };
+        if let Some(command) = command {
+            let signals = self.driver.drive(command);
+            return self.handle_signals(signals);
+        }
         match key {
-            Key::Esc | Key::Char('\n') =>  Result::Ok(false),
+            Key::Alt(c) if c.is_ascii_digit() => {
+                // Alt + the number shown before an ancestor in the breadcrumb
+                let depth = c.to_digit(10).unwrap() as usize;
+                let levels = self.driver.path().len().saturating_sub(depth);
+                let signals = self.driver.drive(DriverCommand::Up(levels));
+                self.handle_signals(signals)
+            },
+            _ => match &token {
+                Some(token) => self.transition(token),
+                None => self.update_views(DriverSignal::NoOp),
+            }
+        }
+    }
+
+    fn transition(&mut self, token: &KeyToken) -> Result<bool> {
+        let signals = self.driver.drive(DriverCommand::Transition(&token.to_string()));
+        self.handle_signals(signals)
+    }
+
+    /// Handles a key press while searching. Typed characters edit the query,
+    /// arrows move the selection, Enter picks the selected result and Esc
+    /// (or Backspace on an empty query) leaves the search.
+    fn handle_search_input(&mut self, key: Key, mut query: String, selected: usize) -> Result<bool> {
+        let signals = match key {
+            Key::Esc => self.driver.drive(DriverCommand::CancelSearch),
+            Key::Char('\n') => self.driver.drive(DriverCommand::PickResult),
+            Key::Up | Key::Ctrl('p') => self.driver.drive(DriverCommand::SelectResult(selected.saturating_sub(1))),
+            Key::Down | Key::Ctrl('n') => self.driver.drive(DriverCommand::SelectResult(selected + 1)),
+            Key::Backspace if query.is_empty() => self.driver.drive(DriverCommand::CancelSearch),
             Key::Backspace => {
-                let signal = self.driver.drive(DriverCommand::Backtrack);
-                self.handle_signal(signal)
+                query.pop();
+                self.driver.drive(DriverCommand::Search(&query))
             },
             Key::Char(c) => {
-                let signal = self.driver.drive(DriverCommand::Transition(&c.to_string()));
-                self.handle_signal(signal)
+                query.push(c);
+                self.driver.drive(DriverCommand::Search(&query))
             },
-            _ => self.update_views(DriverSignal::NoOp),
+            _ => vec![DriverSignal::NoOp],
+        };
+        self.handle_signals(signals)
+    }
+
+    /// Handles a key press while asking for placeholders. Typed characters
+    /// edit the answer, Enter accepts it and Esc stops asking.
+    fn handle_prompt_input(&mut self, key: Key, mut input: String) -> Result<bool> {
+        let signals = match key {
+            Key::Esc => self.driver.drive(DriverCommand::CancelPrompt),
+            Key::Char('\n') => self.driver.drive(DriverCommand::SubmitAnswer),
+            Key::Backspace => {
+                input.pop();
+                self.driver.drive(DriverCommand::Answer(&input))
+            },
+            Key::Char(c) => {
+                input.push(c);
+                self.driver.drive(DriverCommand::Answer(&input))
+            },
+            _ => vec![DriverSignal::NoOp],
+        };
+        self.handle_signals(signals)
+    }
+
+    /// Search starts only from a clean input buffer
+    fn starts_search(&self) -> bool {
+        self.driver.input_buffer().is_empty()
+    }
+
+    /// Handles the signals of a command in order. Stops at the first signal
+    /// which ends the run, discarding the rest.
+    fn handle_signals(&mut self, signals: Vec<DriverSignal<'tree>>) -> Result<bool> {
+        if signals.is_empty() {
+            return self.update_views(DriverSignal::NoOp);
+        }
+        for signal in signals {
+            if !self.handle_signal(signal)? {
+                return Ok(false);
+            }
         }
+        Ok(true)
     }
 
-    fn handle_signal(&mut self, signal: DriverSignal) -> Result<bool> {
+    fn handle_signal(&mut self, signal: DriverSignal<'tree>) -> Result<bool> {
         match &signal {
+            DriverSignal::LeafPicked(tree) if self.executes(tree) => {
+                let tree = *tree;
+                self.update_views(signal)?;
+                self.execute(tree)
+            },
             DriverSignal::LeafPicked(_) => {
                 self.update_views(signal)
                     .map(|repeat| self.loop_mode() && repeat)
             },
+            DriverSignal::DeadEnd(head, typed) if self.flags.contains(&Flags::QuitDeadEnd) => {
+                let message = util::dead_end_message(typed, head);
+                self.update_views(signal)
+                    .and(Err(Error::other(message)))
+            },
             _ => self.update_views(signal),
         }
     }
 
+    /// Checks whether picking `tree` runs its value
+    fn executes(&self, tree: &Tree) -> bool {
+        tree.data().exec || self.flags.contains(&Flags::ExecAll)
+    }
+
+    /// Hands the terminal over to the command of `tree` and waits for it.
+    /// In launcher mode the menu is shown again with the exit status,
+    /// otherwise the run is over and pickem exits like the command.
+    fn execute(&mut self, tree: &Tree) -> Result<bool> {
+        let executor = self.flags.iter()
+            .find_map(|flag| match flag {
+                Flags::Shell(shell) => Some(Executor::new(shell)),
+                _ => None,
+            })
+            .unwrap_or_else(Executor::from_env);
+        for view in self.views.iter_mut() {
+            view.suspend()?;
+        }
+        let status = executor.run(tree.data(), &self.driver.value(tree));
+        for view in self.views.iter_mut() {
+            view.resume()?;
+        }
+        if !self.flags.contains(&Flags::Launcher) {
+            self.exit_code = exec::exit_code(status?);
+            return Ok(false);
+        }
+        let message = match status {
+            Ok(status) => util::exit_message(tree, status),
+            Err(err) => format!("{} could not be run: {}", tree.data().name, err),
+        };
+        let driver = self.driver.clone();
+        self.views.iter_mut()
+            .try_for_each(|view| view.notify(&driver, &message))
+            .map(|_| true)
+    }
+
     /// Checks whether LoopMode flag is set
     fn loop_mode(&self) -> bool {
         self.flags.contains(&Flags::LoopMode)
     }
 
+    /// Returns how long to wait for more keys while a chord is pending
+    fn chord_timeout(&self) -> Option<Duration> {
+        self.flags.iter().find_map(|flag| match flag {
+            Flags::ChordTimeout(ms) => Some(Duration::from_millis(*ms)),
+            _ => None,
+        })
+    }
+
     /// Calls `update` on all views and folds `Result`s 
-    fn update_views(&mut self, signal: DriverSignal) -> Result<(bool)> {
+    fn update_views(&mut self, signal: DriverSignal) -> Result<bool> {
         // FIXME only the first error is preserved. Improve this to
         // maintain all `Err`s
         let driver = self.driver.clone();
         self.views.iter_mut()
             .map(|view| view.update(&driver, &signal))
-            .fold(Ok(()),|acc, e| acc.and(e))
+            .try_fold((), |_, e| e)
             .map(|_| true)
     }
 }
 
 
+/// Converts a key press into the key of a chord, if it can be used in one.
+/// Enter, Esc and Backspace are reserved for controlling pickem.
+fn key_token(key: Key) -> Option<KeyToken> {
+    match key {
+        Key::Char('\n') => None,
+        Key::Char(c) => Some(KeyToken::from(c)),
+        Key::Ctrl(c) => Some(KeyToken::Ctrl(c)),
+        Key::Alt(c) => Some(KeyToken::Alt(c)),
+        Key::F(n) => Some(KeyToken::F(n)),
+        _ => None,
+    }
+}
+
+
 impl<'driver, 'tree, 'view> ControllerTrait for Controller<'driver, 'tree, 'view> {
     /// Iterate over user inputs, handling each one. An `Ok(false)` means run should 
     /// return, `Ok(true)` repeats the loop and an `Error` returns.
-    fn run(&mut self) -> Result<()> {
+    fn run(&mut self) -> Result<i32> {
         self.update_views(DriverSignal::NoOp)?;
-        let stdin = std::io::stdin();
-        let mut keys = stdin.keys();
+        let mut keys = KeyReader::new()?;
         loop {
-            // next() should block until something is input
-            if let Some(key) = keys.next() {
-                match self.handle_input(key.unwrap()) {
-                    Result::Ok(false) => return Result::Ok(()),
-                    Result::Ok(true) => (),
-                    Result::Err(err) => return Result::Err(err),
+            // A pending chord is picked if no key arrives before the timeout,
+            // otherwise block until something is input
+            let timeout = self.chord_timeout().filter(|_| self.driver.pending().is_some());
+            let result = match keys.next(timeout)? {
+                Input::Key(key) => self.handle_input(key),
+                Input::Timeout => {
+                    let signals = self.driver.drive(DriverCommand::Resolve);
+                    self.handle_signals(signals)
+                },
+                Input::Closed => return Result::Ok(self.exit_code),
+            };
+            match result {
+                Result::Ok(false) => return Result::Ok(self.exit_code),
+                Result::Ok(true) => (),
+                Result::Err(err) => {
+                    self.views.iter_mut().for_each(|view| view.abort());
+                    return Result::Err(err);
+                },
+            }
+        }
+    }
+}
+
+
+enum Input {
+    Key(Key),
+    Timeout,
+    Closed,
+}
+
+
+/// Reads key presses from stdin.
+/// Bytes are read and decoded here rather than by termion, which keeps a byte
+/// of its own between reads, so that `poll` is only asked for more input once
+/// every byte read so far has been handed out. Stdin is only read while
+/// waiting for input, which leaves the terminal to commands run in between.
+struct KeyReader {
+    source: File,
+    buffer: VecDeque<u8>,
+}
+
+impl KeyReader {
+    fn new() -> Result<Self> {
+        Ok(Self::from_file(File::open("/dev/stdin")?))
+    }
+
+    fn from_file(source: File) -> Self {
+        Self { source, buffer: VecDeque::new() }
+    }
+
+    /// Blocks until a key is pressed or `timeout` runs out
+    fn next(&mut self, timeout: Option<Duration>) -> Result<Input> {
+        loop {
+            if self.buffer.is_empty() {
+                if let Some(timeout) = timeout {
+                    let mut pollfd = libc::pollfd { fd: self.source.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+                    let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+                    match unsafe { libc::poll(&mut pollfd, 1, millis) } {
+                        0 => return Ok(Input::Timeout),
+                        ready if ready < 0 => return Err(Error::last_os_error()),
+                        _ => (),
+                    }
+                }
+                let mut bytes = [0u8; 64];
+                match self.source.read(&mut bytes)? {
+                    0 => return Ok(Input::Closed),
+                    read => self.buffer.extend(&bytes[..read]),
                 }
             }
+            if let Event::Key(key) = self.decode()? {
+                return Ok(Input::Key(key));
+            }
         }
     }
+
+    /// Decodes the event at the start of the buffer. An escape read on its
+    /// own is the Esc key, otherwise it starts a sequence whose remaining
+    /// bytes are read as needed.
+    fn decode(&mut self) -> Result<Event> {
+        // only called with a non empty buffer
+        let first = self.buffer.pop_front().unwrap_or_default();
+        if first == b'\x1B' && self.buffer.is_empty() {
+            return Ok(Event::Key(Key::Esc));
+        }
+        let (buffer, source) = (&mut self.buffer, &mut self.source);
+        let mut bytes = std::iter::from_fn(|| match buffer.pop_front() {
+            Some(byte) => Some(Ok(byte)),
+            None => {
+                let mut byte = [0u8];
+                match source.read(&mut byte) {
+                    Ok(0) => None,
+                    Ok(_) => Some(Ok(byte[0])),
+                    Err(err) => Some(Err(err)),
+                }
+            },
+        });
+        event::parse_event(first, &mut bytes)
+    }
 }
 
 
@@ -109,6 +390,11 @@ impl<'driver, 'tree, 'view> ControllerTrait for Controller<'driver, 'tree, 'view
 pub struct TUI {
     tty: File,
     backup_termios: Termios,
+    /// Message about the last key press, shown below the user input
+    status: Option<String>,
+    /// Past picks, to list the most used transitions first
+    frecency: Option<Frecency>,
+    theme: Theme,
 }
 
 
@@ -123,11 +409,20 @@ impl TUI {
         let tty_file = format!("/dev/fd/{}", Self::INTERFACE_FD);
         let tty = OpenOptions::new().read(true).write(true).open(tty_file)?;
         let backup_termios = Termios::from_fd(Self::INTERFACE_FD)?;
-        let mut view = TUI { tty, backup_termios };
+        let mut view = TUI { tty, backup_termios, status: None, frecency: None, theme: Theme::default() };
         view.set_cbreak_mode()?;
         Result::Ok(view)
     }
 
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Lists transitions by how often and recently they were picked instead of by chord
+    pub fn order_by_frecency(&mut self, frecency: Frecency) {
+        self.frecency = Some(frecency);
+    }
+
     /// Sets the tty given by `fd` into cbreak_mode
     fn set_cbreak_mode(&mut self) -> Result<()> {
         let cbreak_flags = termios::ICANON | termios::ECHO | termios::ECHOE 
@@ -143,32 +438,162 @@ impl TUI {
 }
 
 
+impl TUI {
+    /// Lines taken by the header above the list of choices
+    const HEADER_HEIGHT: u16 = 3;
+
+    /// Returns the columns and rows of the tty. Stdout is often redirected,
+    /// so the size is read from the interface's fd.
+    fn size(&self) -> Option<(u16, u16)> {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // some ttys report a size of 0 when it's unknown
+        match unsafe { libc::ioctl(Self::INTERFACE_FD, libc::TIOCGWINSZ, &mut size) } {
+            0 if size.ws_col > 0 && size.ws_row > 0 => Some((size.ws_col, size.ws_row)),
+            _ => None,
+        }
+    }
+
+    /// Returns the header bar with the path to the current node and the keys typed
+    fn header(&self, driver: &Driver) -> String {
+        let width = self.size().map(|(width, _)| width as usize);
+        util::pprint_header(&self.theme, driver.root(), driver.path(), driver.input_buffer(), width)
+    }
+
+    /// Draws the search query and the matching leaves, highlighting the selected one
+    fn draw_search(&mut self, driver: &Driver, state: &SearchState) -> Result<()> {
+        let max_results = self.size()
+            .filter(|(_, height)| *height > Self::HEADER_HEIGHT)
+            .map(|(_, height)| (height - Self::HEADER_HEIGHT) as usize)
+            .unwrap_or(20);
+        // scrolls down just enough to keep the selected result in view
+        let first = (state.selected + 1).saturating_sub(max_results);
+        let results = driver.search_results()
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(max_results)
+            .map(|(index, result)| {
+                if index == state.selected {
+                    self.theme.paint(&self.theme.selected, &util::pprint_search_result(&self.theme, result))
+                }
+                else {
+                    util::pprint_search_result(&self.theme, result)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n\r");
+
+        write!(self.tty, "{}{}{}{}/{}{}{}",
+               termion::clear::All,
+               termion::cursor::Goto(1,1),
+               self.header(driver),
+               termion::cursor::Goto(1,2),
+               state.query,
+               termion::cursor::Goto(1,4),
+               results)
+            .and_then(|_| self.tty.flush())
+    }
+
+    /// Draws the value of the leaf being picked and asks for its current placeholder
+    fn draw_prompt(&mut self, driver: &Driver, state: &PromptState) -> Result<()> {
+        write!(self.tty, "{}{}{}{}{}{}{}",
+               termion::clear::All,
+               termion::cursor::Goto(1,1),
+               self.header(driver),
+               termion::cursor::Goto(1,2),
+               state.leaf.data().value,
+               termion::cursor::Goto(1,3),
+               util::pprint_prompt(&self.theme, state))
+            .and_then(|_| self.tty.flush())
+    }
+
+    /// Draws the value of the leaf waiting for confirmation and its question
+    fn draw_confirmation(&mut self, driver: &Driver, tree: &Tree) -> Result<()> {
+        write!(self.tty, "{}{}{}{}{}{}{}",
+               termion::clear::All,
+               termion::cursor::Goto(1,1),
+               self.header(driver),
+               termion::cursor::Goto(1,2),
+               driver.value(tree),
+               termion::cursor::Goto(1,3),
+               util::pprint_confirmation(&self.theme, tree))
+            .and_then(|_| self.tty.flush())
+    }
+}
+
+
 impl View for TUI {
     fn update(&mut self, driver: &Driver, signal: &DriverSignal) -> Result<()> {
-        let mut transitions = driver
-            .get_transitions()
-            .into_iter()
-            .map(|tree| util::pprint_choice(tree))
+        // status is kept until the driver's state changes
+        match signal {
+            DriverSignal::DeadEnd(head, typed) => {
+                self.status = Some(util::pprint_error(&self.theme, &util::dead_end_message(typed, head)));
+            },
+            DriverSignal::Declined(tree) => {
+                self.status = Some(format!("{} was not picked", tree.data().name));
+            },
+            DriverSignal::NoOp => (),
+            _ => self.status = None,
+        };
+        if let Some(tree) = driver.confirmation() {
+            return self.draw_confirmation(driver, tree);
+        }
+        if let Some(state) = driver.prompt() {
+            return self.draw_prompt(driver, state);
+        }
+        if let Some(state) = driver.search() {
+            return self.draw_search(driver, state);
+        }
+        let mut transitions = driver.get_transitions();
+        transitions.retain(|tree| !tree.data().hidden);
+        transitions.sort_by(|a, b| a.data().chord.cmp(&b.data().chord));
+        if let Some(frecency) = &self.frecency {
+            // most used first, ties are kept in chord order
+            let score = |tree: &Tree| frecency.score(&frecency::path_names(driver.path(), tree));
+            transitions.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+        let typed = chord::parse_lossy(driver.input_buffer()).len();
+        let transitions = transitions.into_iter()
+            .map(|tree| util::pprint_choice(&self.theme, tree, typed))
             .collect::<Vec<_>>();
-        transitions.sort();
         let formatted_transitions = transitions.join("\n\r");
+        let status = driver.pending()
+            .map(|tree| util::pprint_pending(&self.theme, tree))
+            .or_else(|| self.status.clone())
+            .unwrap_or_default();
 
         write!(self.tty, "{}{}{}{}{}{}{}",
                termion::clear::All,
                termion::cursor::Goto(1,1),
-               util::pprint_nodes(&driver.path()),
+               self.header(driver),
                termion::cursor::Goto(1,2),
-               util::pprint_user_input(&driver.path(), &driver.input_buffer()),
+               status,
                termion::cursor::Goto(1,4),
                formatted_transitions)
             .and_then(|_| self.tty.flush())
     }
 
+
+    /// Restores the tty's settings and clears the screen for the command
+    fn suspend(&mut self) -> Result<()> {
+        termios::tcsetattr(Self::INTERFACE_FD, termios::TCSANOW, &self.backup_termios)?;
+        write!(self.tty, "{}{}", termion::clear::All, termion::cursor::Goto(1,1))
+            .and_then(|_| self.tty.flush())
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        self.set_cbreak_mode()
+    }
+
+    fn notify(&mut self, driver: &Driver, message: &str) -> Result<()> {
+        self.status = Some(String::from(message));
+        self.update(driver, &DriverSignal::NoOp)
+    }
 }
 
 impl Drop for TUI {
     /// Restore tty's termios settings
-    fn drop(&mut self) -> () {
+    fn drop(&mut self) {
         termios::tcsetattr(Self::INTERFACE_FD, termios::TCSANOW, &self.backup_termios).unwrap();
     }
 }
@@ -181,11 +606,6 @@ pub struct OutputView {
 }
 
 
-pub enum OutputFormat {
-    Value,
-    Signal
-}
-
 pub enum OutputViewFlags {
     OnCleanup,
     OnReceive
@@ -205,29 +625,79 @@ impl View for OutputView {
     /// Formats result and takes care of presenting it to user
     fn update(&mut self, driver: &Driver, signal: &DriverSignal) -> Result<()> {
         // TODO properly handle multiple values to support LoopMode
-        match signal {
-            DriverSignal::NodePicked(tree) | DriverSignal::LeafPicked(tree) => {
-                self.output_buffer = format!("{}", tree.data().value);
+        match (&self.format, signal) {
+            // commands are run instead of printed
+            (OutputFormat::Value, DriverSignal::LeafPicked(tree)) if tree.data().exec => Ok(()),
+            (OutputFormat::Value, DriverSignal::NodePicked(tree))
+                | (OutputFormat::Value, DriverSignal::LeafPicked(tree)) => {
+                self.output_buffer = driver.value(tree);
+                Ok(())
+            },
+            (OutputFormat::Value, DriverSignal::Undone)
+                | (OutputFormat::Value, DriverSignal::Redone) => {
+                self.output_buffer = driver.selections()
+                    .last()
+                    .map(|tree| driver.value(tree))
+                    .unwrap_or_default();
+                Ok(())
+            },
+            (OutputFormat::Signal, DriverSignal::NoOp) => Ok(()),
+            (OutputFormat::Signal, signal) => {
+                self.output_buffer = format!("{:?}", signal);
                 Ok(())
             },
             _ => Ok(())
         }
     }
+
+    fn abort(&mut self) {
+        self.output_buffer.clear();
+    }
 }
 
+
 impl Drop for OutputView {
     fn drop(&mut self) {
-        write!(self.of, "{}", self.output_buffer);
+        let _ = write!(self.of, "{}", self.output_buffer);
+    }
+}
+
+/// Records the leaves picked, saving them once pickem is over
+pub struct FrecencyView {
+    frecency: Frecency,
+}
+
+impl FrecencyView {
+    pub fn new(frecency: Frecency) -> Self {
+        Self { frecency }
+    }
+}
+
+impl View for FrecencyView {
+    fn update(&mut self, driver: &Driver, signal: &DriverSignal) -> Result<()> {
+        if let DriverSignal::LeafPicked(tree) = signal {
+            // recent picks are recorded as picks of the leaf they copy
+            let path = tree.data().origin.clone()
+                .unwrap_or_else(|| frecency::path_names(driver.path(), tree));
+            self.frecency.record(path);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FrecencyView {
+    fn drop(&mut self) {
+        let _ = self.frecency.save();
     }
 }
 
 // TODO move the common helpers to frontend/helpers.rs
 
+#[allow(dead_code)]
 mod view_helpers {
 
-    use termion::{color, AsyncReader};
+    use termion::color;
     use crate::tree::Tree;
-    use crate::tree::LeafData;
 
     /// Converts the selected trees and lingering characters into a
     /// representative string.
@@ -259,8 +729,10 @@ mod view_helpers {
 
 
     #[cfg(test)]
+    #[allow(clippy::needless_borrow)]
     mod tests {
         use super::*;
+        use crate::tree::LeafData;
 
         #[test]
         fn test_pprint_user_input() {
@@ -269,6 +741,7 @@ mod view_helpers {
                 desc: String::from("desc"),
                 chord: String::from("chord"),
                 value: String::from("value"),
+                ..Default::default()
             };
             let tree = Tree::Leaf(data);
             let trees: Vec<&Tree> = vec![&tree];
@@ -282,12 +755,14 @@ mod view_helpers {
                 desc: String::from("root"),
                 chord: String::from("root"),
                 value: String::from("root"),
+                ..Default::default()
             };
             let d2 = LeafData {
                 name: String::from("programs"),
                 desc: String::from("programs"),
                 chord: String::from("programs"),
                 value: String::from("programs"),
+                ..Default::default()
             };
             let root = Tree::Leaf(d1);
             let programs = Tree::Leaf(d2);
@@ -297,3 +772,33 @@ mod view_helpers {
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::io::FromRawFd;
+
+    /// Returns a reader of a pipe and the pipe's other end
+    fn pipe() -> (KeyReader, File) {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let (read, write) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+        (KeyReader::from_file(read), write)
+    }
+
+    #[test]
+    fn test_keys_read_together() {
+        let (mut keys, mut input) = pipe();
+        let timeout = Some(Duration::from_millis(10));
+        input.write_all(b"gg").unwrap();
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Key(Key::Char('g'))));
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Key(Key::Char('g'))));
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Timeout));
+        input.write_all(b"\x1b[A\x1b").unwrap();
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Key(Key::Up)));
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Key(Key::Esc)));
+        drop(input);
+        assert!(matches!(keys.next(timeout).unwrap(), Input::Closed));
+    }
+}
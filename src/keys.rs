@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use termion::event::Key;
+
+use super::chord::{self, KeyToken};
+
+
+/// Controls of pickem which can be bound to keys
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Control {
+    Quit,
+    /// Walks up a level
+    Back,
+    /// Walks back to the root
+    Root,
+    Search,
+    Undo,
+    Redo,
+}
+
+impl Control {
+    pub const NAMES: &'static [&'static str] = &["quit", "back", "root", "search", "undo", "redo"];
+}
+
+impl FromStr for Control {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "quit" => Ok(Control::Quit),
+            "back" => Ok(Control::Back),
+            "root" => Ok(Control::Root),
+            "search" => Ok(Control::Search),
+            "undo" => Ok(Control::Undo),
+            "redo" => Ok(Control::Redo),
+            _ => Err(format!("{} is not a control, use one of {}", s, Control::NAMES.join(", ")))
+        }
+    }
+}
+
+
+/// Keys bound to the controls of pickem
+#[derive(PartialEq, Debug, Clone)]
+pub struct Keybindings {
+    keys: Vec<(Key, Control)>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                (Key::Esc, Control::Quit),
+                (Key::Char('\n'), Control::Quit),
+                (Key::Backspace, Control::Back),
+                (Key::Home, Control::Root),
+                (Key::Char('/'), Control::Search),
+                (Key::Ctrl('u'), Control::Undo),
+                (Key::Ctrl('r'), Control::Redo),
+            ]
+        }
+    }
+}
+
+impl Keybindings {
+    /// Binds `keys` to `control`, replacing the keys it was bound to
+    pub fn bind(&mut self, control: Control, keys: Vec<Key>) {
+        self.keys.retain(|(key, bound)| *bound != control && !keys.contains(key));
+        self.keys.extend(keys.into_iter().map(|key| (key, control)));
+    }
+
+    /// Returns the control bound to `key`, if any
+    pub fn control(&self, key: Key) -> Option<Control> {
+        self.keys.iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, control)| *control)
+    }
+}
+
+
+/// Parses the name of a key, eg. `esc`, `enter`, `home`, or a key
+/// in chord notation, eg. `q`, `<C-c>`, `<F1>`
+pub fn parse_key(name: &str) -> std::result::Result<Key, String> {
+    let key = match name.to_lowercase().as_str() {
+        "esc" => Key::Esc,
+        "enter" => Key::Char('\n'),
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        _ => match chord::parse(name)?.as_slice() {
+            [KeyToken::Char(c)] => Key::Char(*c),
+            [KeyToken::Ctrl(c)] => Key::Ctrl(*c),
+            [KeyToken::Alt(c)] => Key::Alt(*c),
+            [KeyToken::F(n)] => Key::F(*n),
+            [KeyToken::Space] => Key::Char(' '),
+            [KeyToken::Tab] => Key::Char('\t'),
+            _ => return Err(format!("{} is not a single key", name)),
+        }
+    };
+    Ok(key)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("Esc"), Ok(Key::Esc));
+        assert_eq!(parse_key("enter"), Ok(Key::Char('\n')));
+        assert_eq!(parse_key("q"), Ok(Key::Char('q')));
+        assert_eq!(parse_key("<C-c>"), Ok(Key::Ctrl('c')));
+        assert_eq!(parse_key("<Space>"), Ok(Key::Char(' ')));
+        assert!(parse_key("qq").is_err());
+        assert!(parse_key("<C-cc>").is_err());
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut keys = Keybindings::default();
+        keys.bind(Control::Quit, vec![Key::Char('q')]);
+        keys.bind(Control::Search, vec![Key::Char('q'), Key::Ctrl('s')]);
+        // a key is bound to one control only
+        assert_eq!(keys.control(Key::Char('q')), Some(Control::Search));
+        assert_eq!(keys.control(Key::Esc), None);
+        assert_eq!(keys.control(Key::Char('/')), None);
+        assert_eq!(keys.control(Key::Backspace), Some(Control::Back));
+    }
+}
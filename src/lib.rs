@@ -1,9 +1,22 @@
 pub mod tree;
+pub mod chord;
+pub mod keys;
 pub mod parser;
 pub mod driver;
 pub mod util;
 pub mod args;
 pub mod frontend;
+pub mod export;
+pub mod search;
+pub mod placeholder;
+pub mod vars;
+pub mod template;
+pub mod merge;
+pub mod frecency;
+pub mod theme;
+pub mod exec;
+pub mod condition;
+pub mod tags;
 
 pub fn run() {
 
@@ -1,29 +1,106 @@
+use std::io::Result;
+use std::process;
+
 use pickem::tree::Tree;
 use pickem::parser;
+use pickem::export;
 use pickem::frontend::View;
-use pickem::frontend::tui::{Controller, OutputView, TUI, OutputFormat, Flags};
+use pickem::frontend::tui::{Controller, OutputView, FrecencyView, TUI, Flags};
 use pickem::driver::{Driver, DriverFlag};
-use pickem::args::Config;
+use pickem::args::{Config, DeadEndPolicy, Order};
+use pickem::frecency::Frecency;
+use pickem::condition;
+use pickem::exec::Executor;
 use pickem::frontend::Controller as ControllerTrait;
 
 
 fn main() {
-    let config = Config::from_env();
+    let config = Config::from_env().unwrap_or_else(|err| {
+        eprintln!("pickem: {}", err);
+        process::exit(1)
+    });
     let data = config.raw_yaml().unwrap();
     let (tree, violations) = parser::parse(data.as_str());
-    if config.is_dryrun() {
+    if let Some(format) = config.export_format() {
+        print!("{}", export::export(&filter_tree(tree, &config), &format, config.export_depth()));
+    }
+    else if config.is_dryrun() {
         for violation in violations.iter() {
             println!("{:?}", violation);
         }
         println!("{}", tree);
     }
     else {
-        let mut driver = Driver::default(&tree);
-        let mut tui = TUI::new().unwrap();
-        let mut output_view = OutputView::new(OutputFormat::Value).unwrap();
-        let views: Vec<&mut dyn View> = vec![&mut tui, &mut output_view];
-        let flags = vec![Flags::LoopMode];
-        let mut controller = Controller::new(&mut driver, views, flags).unwrap();
-        controller.run().unwrap();
+        match run_selector(tree, &config) {
+            Ok(code) => process::exit(code),
+            Err(err) => {
+                // views are dropped by now, so the terminal is restored
+                eprintln!("pickem: {}", err);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Drops the trees which don't belong on this machine or aren't tagged
+/// as asked, dry runs show them all
+fn filter_tree(tree: Tree, config: &Config) -> Tree {
+    let executor = config.shell()
+        .map(|shell| Executor::new(&shell))
+        .unwrap_or_else(Executor::from_env);
+    config.tag_filter().apply(condition::prune(tree, &executor))
+}
+
+/// Runs the interactive selector over `tree`, returns the code to exit with
+fn run_selector(tree: Tree, config: &Config) -> Result<i32> {
+    // past picks are a nicety, an unreadable store is treated as empty
+    let mut frecency = config.menu_path()
+        .and_then(Frecency::data_file)
+        .and_then(|file| Frecency::load(&file).ok())
+        .unwrap_or_default();
+    // picks of filtered out leaves are kept for other runs
+    frecency.prune(&tree);
+    let tree = filter_tree(tree, config);
+    let tree = if config.shows_recent() { frecency.with_recent(tree) } else { tree };
+
+    let mut driver_flags = Vec::new();
+    let mut flags = vec![Flags::LoopMode];
+    if let Some(timeout) = config.chord_timeout() {
+        driver_flags.push(DriverFlag::ChordTimeout);
+        flags.push(Flags::ChordTimeout(timeout));
+    }
+    match config.dead_end_policy() {
+        DeadEndPolicy::Ignore => driver_flags.push(DriverFlag::IgnoreDeadEnd),
+        DeadEndPolicy::Clear => (),
+        DeadEndPolicy::Root => driver_flags.push(DriverFlag::RootOnDeadEnd),
+        DeadEndPolicy::Quit => flags.push(Flags::QuitDeadEnd),
+    }
+    if config.is_launcher() {
+        flags.push(Flags::Launcher);
+    }
+    if let Some(shell) = config.shell() {
+        flags.push(Flags::Shell(shell));
+    }
+    let mut driver = Driver::new(&tree, driver_flags);
+    let mut tui = TUI::new()?;
+    tui.set_theme(config.theme());
+    if config.order() == Order::Frecency {
+        tui.order_by_frecency(frecency.clone());
+    }
+    let mut output_view = OutputView::new(config.output_format())?;
+    let mut frecency_view = config.records_history().then(|| FrecencyView::new(frecency));
+    let mut views: Vec<&mut dyn View> = vec![&mut tui];
+    if let Some(frecency_view) = &mut frecency_view {
+        views.push(frecency_view);
+    }
+    // in exec mode values are run rather than printed
+    if config.is_exec() {
+        flags.push(Flags::ExecAll);
+    }
+    else {
+        views.push(&mut output_view);
     }
+    let mut controller = Controller::new(&mut driver, views, flags)?;
+    controller.set_keybindings(config.keybindings());
+    controller.run()
 }
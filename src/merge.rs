@@ -0,0 +1,117 @@
+use yaml_rust::Yaml;
+use yaml_rust::yaml::Hash;
+
+use super::parser::Violation;
+
+/// YAML's merge key
+pub const MERGE_KEY: &str = "<<";
+/// Reserved key of the root holding mappings only used through aliases
+pub const ANCHORS_KEY: &str = ".anchors";
+
+
+/// Applies YAML merge keys: `<<: *base` copies the entries of `base` into the
+/// mapping holding it, unless the mapping has an entry with the same key.
+/// With a list, `<<: [*a, *b]`, mappings earlier in the list take precedence.
+/// The root's `.anchors` is dropped once merged.
+pub fn expand(root: &Yaml) -> (Yaml, Vec<Violation>) {
+    let mut violations = Vec::new();
+    let mut expanded = expand_node("root", root, &mut violations);
+    if let Yaml::Hash(hash) = &mut expanded {
+        hash.remove(&Yaml::String(String::from(ANCHORS_KEY)));
+    }
+    (expanded, violations)
+}
+
+fn expand_node(name: &str, node: &Yaml, violations: &mut Vec<Violation>) -> Yaml {
+    match node {
+        Yaml::Hash(hash) => Yaml::Hash(expand_hash(name, hash, violations)),
+        Yaml::Array(array) => Yaml::Array(array.iter().map(|node| expand_node(name, node, violations)).collect()),
+        other => other.clone(),
+    }
+}
+
+fn expand_hash(name: &str, hash: &Hash, violations: &mut Vec<Violation>) -> Hash {
+    let merge_key = Yaml::String(String::from(MERGE_KEY));
+    let mut expanded = Hash::new();
+    for (key, value) in hash.iter() {
+        if *key != merge_key {
+            let child_name = key.as_str().unwrap_or(name);
+            expanded.insert(key.clone(), expand_node(child_name, value, violations));
+            continue;
+        }
+        let sources = match value {
+            Yaml::Hash(_) => vec![value],
+            Yaml::Array(array) if array.iter().all(|source| source.as_hash().is_some()) => array.iter().collect(),
+            _ => {
+                violations.push(Violation {
+                    parent_name: String::from(name),
+                    child_name: String::from(MERGE_KEY),
+                    violation: String::from("merge keys take a mapping or a list of mappings, eg. <<: *base")
+                });
+                continue;
+            }
+        };
+        for source in sources {
+            // sources may merge other mappings themselves
+            if let Yaml::Hash(source) = expand_node(name, source, violations) {
+                for (key, value) in source {
+                    if !hash.contains_key(&key) && !expanded.contains_key(&key) {
+                        expanded.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+    expanded
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parser;
+
+    #[test]
+    fn test_merge_precedence() {
+        let yml = "
+.anchors:
+  base: &base
+    status:
+      .chord: s
+      .value: git status
+    log:
+      .chord: l
+      .value: git log
+  extra: &extra
+    log:
+      .chord: l
+      .value: git log --oneline
+    diff:
+      .chord: d
+      .value: git diff
+git:
+  .chord: g
+  <<: [*base, *extra]
+  status:
+    .chord: s
+    .value: git status --short
+";
+        let (tree, violations) = parser::parse(yml);
+        let git = tree.children()[&"g"];
+        assert_eq!(git.children().len(), 3);
+        // the mapping's own entries win, then the first merged mapping
+        assert_eq!(git.children()[&"s"].data().value, "git status --short");
+        assert_eq!(git.children()[&"l"].data().value, "git log");
+        assert_eq!(git.children()[&"d"].data().value, "git diff");
+        assert!(violations.is_empty());
+        assert_eq!(tree.children().len(), 1);
+    }
+
+    #[test]
+    fn test_merge_of_scalar_is_a_violation() {
+        let (tree, violations) = parser::parse("git:\n  <<: nothing\n  .chord: g\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].parent_name, "git");
+        assert_eq!(violations[0].child_name, "<<");
+        assert!(tree.children()[&"g"].is_leaf());
+    }
+}
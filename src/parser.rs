@@ -1,15 +1,43 @@
 use yaml_rust::{YamlLoader, Yaml};
 
 use super::tree::{Tree, LeafData};
+use super::chord;
+use super::placeholder;
+use super::vars::{self, Vars};
+use super::template;
+use super::merge;
+use super::theme::Style;
+use super::condition;
+use super::tags;
+
+static RESERVED_KEYS: &[&str] = &[
+    ".value", ".chord", ".desc", ".exec", ".run", ".cwd", ".env", ".args", ".confirm", ".color", ".icon",
+    ".hidden", ".when", ".tags",
+    ".vars", ".templates", ".template", ".params", ".anchors",
+];
+
+/// Reserved keys which mark a leaf as a command, either with a boolean or the command itself
+static EXEC_KEYS: &[&str] = &[".exec", ".run"];
+
+/// Reserved keys which may have placeholders
+static VALUE_KEYS: &[&str] = &[".value", ".exec", ".run"];
+
+/// Reserved keys whose `${name}` are expanded
+static EXPANDED_KEYS: &[&str] = &[".value", ".exec", ".run", ".desc", ".cwd"];
+
+/// Reserved keys which only mean something at the root
+static ROOT_KEYS: &[&str] = &[".vars", ".templates", ".anchors"];
+
+/// Reserved keys whose booleans aren't turned into strings
+static BOOLEAN_KEYS: &[&str] = &[".exec", ".run", ".confirm", ".hidden"];
 
-static RESERVED_KEYS: &[&str] = &[".value", ".chord", ".desc"];
 
 ///Identifies a violating node by its parent and its name, respectively
 #[derive(Debug)]
 pub struct Violation {
-    parent_name: String,
-    child_name: String,
-    violation: String
+    pub parent_name: String,
+    pub child_name: String,
+    pub violation: String
 }
 
 pub enum Flags {
@@ -18,7 +46,7 @@ pub enum Flags {
 
 enum NodeType<'a> {
     Violator(Violation),
-    Value(String),
+    Value,
     Child(NamedNode<'a>)
 }
 
@@ -29,10 +57,120 @@ type NamedNode<'a> = (&'a str, &'a Yaml);
 ///Takes a yaml node that belongs to a parent and defines a type to it.
 fn child_or_violator<'a>(parent_name: &'a str, child_name: &'a str, child: &'a Yaml) -> NodeType<'a> {
     match child {
+        _ if child_name.starts_with('.') && !RESERVED_KEYS.contains(&child_name) => {
+            let violation = Violation {
+                parent_name: String::from(parent_name),
+                child_name: String::from(child_name),
+                violation: unknown_key_message(child_name)
+            };
+            NodeType::Violator(violation)
+        },
+        // the root's are taken out before the tree is built
+        _ if ROOT_KEYS.contains(&child_name) => {
+            let violation = Violation {
+                parent_name: String::from(parent_name),
+                child_name: String::from(child_name),
+                violation: format!("{} is only read at the root of the menu", child_name)
+            };
+            NodeType::Violator(violation)
+        },
+        Yaml::Hash(env) if child_name == ".env" => {
+            let invalid = env.iter().find(|(key, value)| key.as_str().is_none() || value.as_str().is_none());
+            match invalid {
+                None => NodeType::Value,
+                Some(_) => {
+                    let violation = Violation {
+                        parent_name: String::from(parent_name),
+                        child_name: String::from(child_name),
+                        violation: String::from("environment variables and their values must be strings")
+                    };
+                    NodeType::Violator(violation)
+                }
+            }
+        },
+        Yaml::Hash(args) if child_name == ".args" => {
+            match args.iter().find(|(key, value)| key.as_str().is_none() || value.as_str().is_none()) {
+                None => NodeType::Value,
+                Some(_) => {
+                    let violation = Violation {
+                        parent_name: String::from(parent_name),
+                        child_name: String::from(child_name),
+                        violation: String::from("placeholders and their descriptions must be strings")
+                    };
+                    NodeType::Violator(violation)
+                }
+            }
+        },
+        _ if child_name == condition::WHEN_KEY => match condition::from_yaml(child) {
+            Ok(_) => NodeType::Value,
+            Err(err) => {
+                let violation = Violation {
+                    parent_name: String::from(parent_name),
+                    child_name: String::from(child_name),
+                    violation: err
+                };
+                NodeType::Violator(violation)
+            }
+        },
+        _ if child_name == tags::TAGS_KEY => match tags::from_yaml(child) {
+            Ok(_) => NodeType::Value,
+            Err(err) => {
+                let violation = Violation {
+                    parent_name: String::from(parent_name),
+                    child_name: String::from(child_name),
+                    violation: err
+                };
+                NodeType::Violator(violation)
+            }
+        },
         Yaml::Hash(_) => NodeType::Child((child_name, child)),
-        Yaml::String(value) => {
+        Yaml::Array(pair) if is_ambiguous_pair(pair) => {
+            let violation = Violation {
+                parent_name: String::from(parent_name),
+                child_name: String::from(child_name),
+                violation: format!("[{}, {}] is either a chord and a value or a list of two values, \
+                                    write it as a mapping with .chord and .value or as a list of mappings",
+                                   pair[0].as_str().unwrap_or_default(), pair[1].as_str().unwrap_or_default())
+            };
+            NodeType::Violator(violation)
+        },
+        Yaml::Boolean(_) if BOOLEAN_KEYS.contains(&child_name) => NodeType::Value,
+        Yaml::String(value) if child_name == ".chord" => match chord::parse(value) {
+            Ok(_) => NodeType::Value,
+            Err(err) => {
+                let violation = Violation {
+                    parent_name: String::from(parent_name),
+                    child_name: String::from(child_name),
+                    violation: err
+                };
+                NodeType::Violator(violation)
+            }
+        },
+        Yaml::String(value) if child_name == ".color" => match value.parse::<Style>() {
+            Ok(_) => NodeType::Value,
+            Err(err) => {
+                let violation = Violation {
+                    parent_name: String::from(parent_name),
+                    child_name: String::from(child_name),
+                    violation: err
+                };
+                NodeType::Violator(violation)
+            }
+        },
+        Yaml::String(value) if VALUE_KEYS.contains(&child_name) => match placeholder::validate(value) {
+            Ok(_) => NodeType::Value,
+            Err(err) => {
+                let violation = Violation {
+                    parent_name: String::from(parent_name),
+                    child_name: String::from(child_name),
+                    violation: err
+                };
+                NodeType::Violator(violation)
+            }
+        },
+        Yaml::String(_) => {
             if RESERVED_KEYS.contains(&child_name) {
-                NodeType::Value(String::from(value))
+                NodeType::Value
             }
             else {
                 let violation = Violation {
@@ -47,13 +185,41 @@ fn child_or_violator<'a>(parent_name: &'a str, child_name: &'a str, child: &'a Y
                 let violation = Violation {
                     parent_name: String::from(parent_name),
                     child_name: String::from(child_name),
-                    violation: format!("The value of every YAML node must be a hash (asside from reserved keys)")
+                    violation: String::from("The value of every YAML node must be a hash, a value, a [chord, value] pair or a list of values (asside from reserved keys)")
                 };
                 NodeType::Violator(violation)
         }
     }
 }
 
+///Explains that `key` isn't reserved, suggesting the closest reserved key
+fn unknown_key_message(key: &str) -> String {
+    let closest = RESERVED_KEYS.iter()
+        .map(|reserved| (edit_distance(key, reserved), reserved))
+        .min();
+    match closest {
+        Some((distance, reserved)) if distance <= 2 => {
+            format!("{} is not a reserved key, did you mean {}?", key, reserved)
+        },
+        _ => format!("{} is not a reserved key, reserved keys are {}", key, RESERVED_KEYS.join(", ")),
+    }
+}
+
+///Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 fn list_of_pairs_into_pair_of_lists<T, U>(list: Vec<(T, U)>) -> (Vec<T>, Vec<U>) {
     let mut ts: Vec<T> = Vec::new();
     let mut us: Vec<U> = Vec::new();
@@ -65,7 +231,7 @@ fn list_of_pairs_into_pair_of_lists<T, U>(list: Vec<(T, U)>) -> (Vec<T>, Vec<U>)
 }
 
 
-fn node_to_tree(name: &str, node: &Yaml) -> (Tree, Vec<Violation>) {
+fn node_to_tree(name: &str, node: &Yaml, vars: &Vars) -> (Tree, Vec<Violation>) {
     let classified_nodes =  children(name, node);
     let mut violations: Vec<Violation> = Vec::new();
     let mut children: Vec<NamedNode> = Vec::new();
@@ -73,30 +239,66 @@ fn node_to_tree(name: &str, node: &Yaml) -> (Tree, Vec<Violation>) {
         match node {
             NodeType::Violator(violation) => violations.push(violation),
             NodeType::Child(named_node) => children.push(named_node),
-            NodeType::Value(value) => ()
+            NodeType::Value => ()
         }
     }
     let (trees, mut nested_violations) = list_of_pairs_into_pair_of_lists(children.into_iter()
-        .map(uncurried_node_to_tree)
+        .map(|named_node| uncurried_node_to_tree(named_node, vars))
         .collect::<Vec<(Tree, Vec<Violation>)>>());
+    violations.extend(shadowed_chords(name, &trees));
+    violations.extend(undefined_variables(name, node, vars));
     nested_violations.push(violations);
     let violations: Vec<Violation> = nested_violations.into_iter().flatten().collect();
 
-    let tree;
-    let data = build_data(node, name);
-    if trees.is_empty() {
-        tree = Tree::Leaf(data);
+    let data = build_data(node, name, vars);
+    let tree = if trees.is_empty() {
+        Tree::Leaf(data)
     }
     else {
-        tree = Tree::Node(data, trees)
-    }
+        Tree::Node(data, trees)
+    };
     (tree, violations)
 }
 
+///Flags siblings whose chord starts with the whole chord of another sibling.
+///Such chords are unreachable unless pickem waits for longer chords.
+fn shadowed_chords(parent_name: &str, siblings: &[Tree]) -> Vec<Violation> {
+    let keys = siblings.iter()
+        .map(|tree| chord::parse_lossy(&tree.data().chord))
+        .collect::<Vec<_>>();
+    let mut violations = Vec::new();
+    for (shadowed, shadowed_keys) in siblings.iter().zip(keys.iter()) {
+        let shadowing = siblings.iter().zip(keys.iter())
+            .find(|(_, keys)| keys.len() < shadowed_keys.len() && shadowed_keys.starts_with(keys));
+        if let Some((tree, _)) = shadowing {
+            violations.push(Violation {
+                parent_name: String::from(parent_name),
+                child_name: shadowed.data().name.clone(),
+                violation: format!("chord {} starts with the chord of {}, it is only reachable with --timeout",
+                                   shadowed.data().chord, tree.data().name)
+            });
+        }
+    }
+    violations
+}
+
+///Flags the `${name}` of a node's expanded keys which are neither declared
+///nor in the environment
+fn undefined_variables(name: &str, node: &Yaml, vars: &Vars) -> Vec<Violation> {
+    EXPANDED_KEYS.iter()
+        .filter_map(|key| Some((*key, node[*key].as_str()?)))
+        .flat_map(|(key, text)| vars.undefined(text).into_iter().map(move |var| Violation {
+            parent_name: String::from(name),
+            child_name: String::from(key),
+            violation: vars::undefined_message(&var)
+        }))
+        .collect()
+}
+
 ///Uncurried version of node_to_tree
-fn uncurried_node_to_tree(named_node: NamedNode) -> (Tree, Vec<Violation>){
+fn uncurried_node_to_tree(named_node: NamedNode, vars: &Vars) -> (Tree, Vec<Violation>){
     let (name, node) = named_node;
-    node_to_tree(name, node)
+    node_to_tree(name, node, vars)
 }
 
 ///Gets children for a node and calls child_or_violator on all of them
@@ -104,9 +306,14 @@ fn children<'a>(parent_name: &'a str, node: &'a Yaml) -> Vec<NodeType<'a>> {
     //should be a safe operation because the parent *should* only call this
     //for NodeType::Children values
     let hash = node.as_hash().unwrap();
-    let f = |(key, value): (&'a Yaml, &'a Yaml)| {
-        let node_name = key.as_str().unwrap(); //also should be safe
-        child_or_violator(parent_name, node_name, value)
+    let f = |(key, value): (&'a Yaml, &'a Yaml)| match key.as_str() {
+        Some(node_name) => child_or_violator(parent_name, node_name, value),
+        // scalars were turned into strings by `coerce_scalars`
+        None => NodeType::Violator(Violation {
+            parent_name: String::from(parent_name),
+            child_name: format!("{:?}", key),
+            violation: String::from("names must be strings")
+        }),
     };
     hash.iter()
         .map(f)
@@ -119,14 +326,184 @@ fn attr_getter<'a>(node: &'a Yaml, attr: &'a str, default: &'a str) -> &'a str {
 
 }
 
-///Builder method to convert fields in an yaml node to `TreeData`
-fn build_data(node: &Yaml, name: &str) -> LeafData { 
+///Returns whether a node is a command and the command itself, if
+///given by `.exec` or `.run` instead of `.value`
+fn exec_getter(node: &Yaml) -> (bool, Option<&str>) {
+    for key in EXEC_KEYS {
+        match &node[*key] {
+            Yaml::String(command) => return (true, Some(command.as_str())),
+            Yaml::Boolean(exec) => return (*exec, None),
+            _ => (),
+        }
+    }
+    (false, None)
+}
+
+///Returns the question asked before picking a node. `.confirm` is either
+///`true`, to ask a generic question, or the question itself
+fn confirm_getter(node: &Yaml, name: &str) -> Option<String> {
+    match &node[".confirm"] {
+        Yaml::String(question) => Some(question.clone()),
+        Yaml::Boolean(true) => Some(format!("Really pick {}?", name)),
+        _ => None,
+    }
+}
+
+///Returns the string pairs of a reserved hash, such as `.env`
+fn pairs_getter(node: &Yaml, attr: &str) -> Vec<(String, String)> {
+    node[attr].as_hash()
+        .map(|env| env.iter()
+             .filter_map(|(key, value)| Some((String::from(key.as_str()?), String::from(value.as_str()?))))
+             .collect())
+        .unwrap_or_default()
+}
+
+///Builder method to convert fields in an yaml node to `TreeData`.
+///Unknown variables are left as is, they are reported by `undefined_variables`.
+fn build_data(node: &Yaml, name: &str, vars: &Vars) -> LeafData { 
+    let (exec, command) = exec_getter(node);
+    let expand = |text: &str| vars.expand(text);
     LeafData {
         name: String::from(name),
-        value: String::from(attr_getter(node, &".value", name)),
-        chord: String::from(attr_getter(node, &".chord", name)),
-        desc: String::from(attr_getter(node, &".desc", name))
+        value: expand(command.unwrap_or_else(|| attr_getter(node, ".value", name))),
+        chord: String::from(attr_getter(node, ".chord", name)),
+        desc: expand(attr_getter(node, ".desc", name)),
+        exec,
+        cwd: node[".cwd"].as_str().map(expand),
+        env: pairs_getter(node, ".env"),
+        args: pairs_getter(node, ".args"),
+        confirm: confirm_getter(node, name),
+        color: node[".color"].as_str().filter(|color| color.parse::<Style>().is_ok()).map(String::from),
+        icon: node[".icon"].as_str().map(String::from),
+        hidden: node[".hidden"].as_bool().unwrap_or(false),
+        when: condition::from_yaml(&node[condition::WHEN_KEY]).unwrap_or_default(),
+        tags: tags::from_yaml(&node[tags::TAGS_KEY]).unwrap_or_default(),
+        origin: None,
+    }
+}
+
+
+///Turns numbers into strings and, except for the keys which take
+///booleans, booleans too. eg. `.chord: 1` is the chord `1`.
+fn coerce_scalars(node: &Yaml, key: &str) -> Yaml {
+    match node {
+        Yaml::Hash(hash) => Yaml::Hash(hash.iter()
+            .map(|(child_key, value)| {
+                let child_key = coerce_scalars(child_key, "");
+                let value = coerce_scalars(value, child_key.as_str().unwrap_or_default());
+                (child_key, value)
+            })
+            .collect()),
+        Yaml::Array(items) => Yaml::Array(items.iter().map(|item| coerce_scalars(item, "")).collect()),
+        Yaml::Integer(number) => Yaml::String(number.to_string()),
+        Yaml::Real(number) => Yaml::String(number.clone()),
+        Yaml::Boolean(boolean) if !BOOLEAN_KEYS.contains(&key) => Yaml::String(boolean.to_string()),
+        other => other.clone(),
+    }
+}
+
+///Rewrites the compact forms of leaves into mappings:
+///`name: value` becomes `name: {.value: value}` and
+///`name: [chord, value]` becomes `name: {.chord: chord, .value: value}` (see `is_pair`)
+///and any other list becomes a node with a leaf per item (see `list_yaml`).
+///Values of reserved keys are kept as is.
+fn expand_shorthands(node: &Yaml) -> Yaml {
+    let hash = match node {
+        Yaml::Hash(hash) => hash,
+        other => return other.clone(),
+    };
+    let expanded = hash.iter()
+        .map(|(key, value)| {
+            let reserved = key.as_str().is_none_or(|key| key.starts_with('.'));
+            let value = match value {
+                _ if reserved => value.clone(),
+                Yaml::String(_) => leaf_yaml(None, value),
+                Yaml::Array(pair) if is_pair(pair) => leaf_yaml(Some(&pair[0]), &pair[1]),
+                // reported by `child_or_violator`
+                Yaml::Array(pair) if is_ambiguous_pair(pair) => value.clone(),
+                Yaml::Array(items) => list_yaml(items).unwrap_or_else(|| value.clone()),
+                _ => expand_shorthands(value),
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    Yaml::Hash(expanded)
+}
+
+///Tells a `[chord, value]` pair from a list of two values: the chord is a
+///single key and the value isn't, eg. `[l, git log]` and `[<C-t>, htop]`
+///but not `[qa, prod]`. Longer chords are given with `.chord`.
+fn is_pair(items: &[Yaml]) -> bool {
+    match items {
+        [chord, value] => is_single_key(chord) && !is_single_key(value),
+        _ => false,
+    }
+}
+
+///Whether a list of two values could be a pair as well as a list, eg. `[a, b]`
+fn is_ambiguous_pair(items: &[Yaml]) -> bool {
+    match items {
+        [chord, value] => is_single_key(chord) && is_single_key(value),
+        _ => false,
+    }
+}
+
+fn is_single_key(item: &Yaml) -> bool {
+    item.as_str()
+        .and_then(|item| chord::parse(item).ok())
+        .is_some_and(|keys| keys.len() == 1)
+}
+
+///Keys given, in order, to the items of a list which don't have a chord
+static LIST_CHORDS: &str = "1234567890abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+///Builds the mapping of a node from a list of leaves. Items are either values
+///or mappings with reserved keys, and are named after their value without
+///its leading `.`s.
+///Items get the first key from `LIST_CHORDS` not used by another item.
+///Returns `None` if an item is neither, or if the list runs out of keys.
+fn list_yaml(items: &[Yaml]) -> Option<Yaml> {
+    let mut taken = items.iter()
+        .filter_map(|item| item[".chord"].as_str())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let mut node = yaml_rust::yaml::Hash::new();
+    for item in items {
+        let mut leaf = match item {
+            Yaml::String(_) => leaf_yaml(None, item),
+            Yaml::Hash(_) if item[".value"].as_str().is_some() => item.clone(),
+            _ => return None,
+        };
+        if leaf[".chord"].is_badvalue() {
+            let chord = LIST_CHORDS.chars()
+                .map(String::from)
+                .find(|chord| !taken.contains(chord))?;
+            taken.push(chord.clone());
+            if let Yaml::Hash(leaf) = &mut leaf {
+                leaf.insert(Yaml::String(String::from(".chord")), Yaml::String(chord));
+            }
+        }
+        // names can't start with a `.`, which is kept for reserved keys, and
+        // items with the same name are told apart by their chord
+        let chord = leaf[".chord"].as_str().unwrap_or_default();
+        let value = leaf[".value"].as_str().unwrap_or_default().trim_start_matches('.');
+        let mut name = String::from(if value.is_empty() { chord } else { value });
+        if node.contains_key(&Yaml::String(name.clone())) {
+            name = format!("{} ({})", name, chord);
+        }
+        node.insert(Yaml::String(name), leaf);
+    }
+    Some(Yaml::Hash(node))
+}
+
+///Builds the mapping of a leaf from its chord and value
+fn leaf_yaml(chord: Option<&Yaml>, value: &Yaml) -> Yaml {
+    let mut leaf = yaml_rust::yaml::Hash::new();
+    if let Some(chord) = chord {
+        leaf.insert(Yaml::String(String::from(".chord")), chord.clone());
     }
+    leaf.insert(Yaml::String(String::from(".value")), value.clone());
+    Yaml::Hash(leaf)
 }
 
 
@@ -134,7 +511,23 @@ fn build_data(node: &Yaml, name: &str) -> LeafData {
 pub fn parse(yml: &str) -> (Tree, Vec<Violation>) {
     let loaded_yaml  = YamlLoader::load_from_str(yml).unwrap();
     let yaml = &loaded_yaml[0];
-    node_to_tree("root", &yaml)
+    let yaml = coerce_scalars(yaml, "");
+    let (yaml, mut violations) = merge::expand(&yaml);
+    let (yaml, template_violations) = template::expand(&yaml);
+    violations.extend(template_violations);
+    let mut yaml = expand_shorthands(&yaml);
+    let (vars, errors) = Vars::from_yaml(&yaml[".vars"]);
+    if let Yaml::Hash(hash) = &mut yaml {
+        hash.remove(&Yaml::String(String::from(".vars")));
+    }
+    let (tree, tree_violations) = node_to_tree("root", &yaml, &vars);
+    violations.extend(tree_violations);
+    violations.extend(errors.into_iter().map(|(name, err)| Violation {
+        parent_name: String::from(".vars"),
+        child_name: name,
+        violation: err
+    }));
+    (tree, violations)
 }
 
 
@@ -142,7 +535,7 @@ pub fn parse(yml: &str) -> (Tree, Vec<Violation>) {
 mod tests {
     use super::*;
 
-    fn get_test_yml<'a>() -> Vec<Yaml> {
+    fn get_test_yml() -> Vec<Yaml> {
         let raw = 
 "
 foo:
@@ -173,6 +566,253 @@ bar:
     fn get_violators_return_violations() {
     }
 
+    #[test]
+    fn test_exec_keys() {
+        let yml = "
+build:
+  .run: make
+  .cwd: /tmp
+  .env:
+    CC: clang
+test:
+  .value: make test
+  .exec: true
+status:
+  .value: git status
+bad:
+  .run: make
+  .env:
+    CC:
+      - clang
+";
+        let (tree, violations) = parse(yml);
+        let build = tree.children()[&"build"].data();
+        assert_eq!(build.value, "make");
+        assert!(build.exec);
+        assert_eq!(build.cwd, Some(String::from("/tmp")));
+        assert_eq!(build.env, vec![(String::from("CC"), String::from("clang"))]);
+        assert!(tree.children()[&"test"].data().exec);
+        assert!(!tree.children()[&"status"].data().exec);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].parent_name, "bad");
+        assert_eq!(violations[0].child_name, ".env");
+    }
+
+    #[test]
+    fn test_shadowed_chord_is_a_violation() {
+        let (_, violations) = parse("go:\n  .chord: g\ngoto:\n  .chord: gg\nx:\n  .chord: x\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].parent_name, "root");
+        assert_eq!(violations[0].child_name, "goto");
+    }
+
+    #[test]
+    fn test_malformed_chord_is_a_violation() {
+        let (_, violations) = parse("grep:\n  .chord: <C-gg>s\nfind:\n  .chord: <C-f>\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].parent_name, "grep");
+        assert_eq!(violations[0].child_name, ".chord");
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let yml = "
+checkout:
+  .value: git checkout {?branch:main}
+  .args:
+    branch: branch to check out
+clean:
+  .run: find . -name '*.orig' -exec rm {} \\;
+awk:
+  .value: awk '{print $1}'
+logs:
+  .run: kubectl logs {?pod
+";
+        let (tree, violations) = parse(yml);
+        let checkout = tree.children()[&"checkout"].data();
+        assert_eq!(checkout.args, vec![(String::from("branch"), String::from("branch to check out"))]);
+        assert_eq!(tree.children()[&"awk"].data().value, "awk '{print $1}'");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].parent_name, "logs");
+        assert_eq!(violations[0].child_name, ".run");
+    }
+
+    #[test]
+    fn test_confirm() {
+        let yml = "
+reset:
+  .value: git reset --hard
+  .confirm: Discard every change?
+delete:
+  .run: kubectl delete pod {?pod}
+  .confirm: true
+status:
+  .value: git status
+  .confirm: false
+";
+        let (tree, violations) = parse(yml);
+        assert!(violations.is_empty());
+        let children = tree.children();
+        assert_eq!(children[&"reset"].data().confirm, Some(String::from("Discard every change?")));
+        assert_eq!(children[&"delete"].data().confirm, Some(String::from("Really pick delete?")));
+        assert_eq!(children[&"status"].data().confirm, None);
+    }
+
+    #[test]
+    fn test_color_and_icon() {
+        let yml = "
+git:
+  .chord: g
+  .color: bold green
+  .icon: ⎇
+  status: git status
+ls:
+  .value: ls
+  .color: purple
+";
+        let (tree, violations) = parse(yml);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].child_name, ".color");
+        let children = tree.children();
+        assert_eq!(children[&"g"].data().color, Some(String::from("bold green")));
+        assert_eq!(children[&"g"].data().icon, Some(String::from("⎇")));
+        assert_eq!(children[&"ls"].data().color, None);
+    }
+
+    #[test]
+    fn test_vars() {
+        let yml = "
+.vars:
+  projects: /srv/projects
+  editor: vim
+pickem:
+  .desc: open ${projects}/pickem
+  .run: ${editor} src/lib.rs
+  .cwd: ${projects}/pickem
+shell:
+  .value: kill $$ $${pid} $${editor}
+typo:
+  .run: cd ${PICKEM_UNDEFINED_VARIABLE}
+nested:
+  .vars:
+    editor: emacs
+  edit: ${editor}
+";
+        let (tree, violations) = parse(yml);
+        let children = tree.children();
+        let pickem = children[&"pickem"].data();
+        assert_eq!(pickem.desc, "open /srv/projects/pickem");
+        assert_eq!(pickem.value, "vim src/lib.rs");
+        assert_eq!(pickem.cwd, Some(String::from("/srv/projects/pickem")));
+        assert_eq!(children[&"shell"].data().value, "kill $$ ${pid} ${editor}");
+        assert_eq!(children[&"nested"].children()[&"edit"].data().value, "vim");
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|violation| violation.parent_name == "typo" && violation.child_name == ".run"));
+        assert!(violations.iter().any(|violation| violation.parent_name == "nested" && violation.child_name == ".vars"));
+    }
+
+    #[test]
+    fn test_shorthand_leaves() {
+        let yml = "
+git:
+  .chord: g
+  status: git status
+  log: [l, git log --oneline]
+  top: [<C-t>, htop]
+  .desc: git commands
+";
+        let (tree, violations) = parse(yml);
+        assert!(violations.is_empty());
+        let git = tree.children()[&"g"];
+        assert_eq!(git.data().desc, "git commands");
+        assert_eq!(git.children()[&"status"].data().value, "git status");
+        assert_eq!(git.children()[&"l"].data().value, "git log --oneline");
+        assert_eq!(git.children()[&"<C-t>"].data().value, "htop");
+
+        // the serialized tree reads back the same
+        let (reparsed, _) = parse(&tree.to_string());
+        assert_eq!(reparsed.children()[&"root"], &tree);
+    }
+
+    #[test]
+    fn test_list_leaves() {
+        let yml = "
+hosts:
+  - prod.example.com
+  - .value: staging.example.com
+    .chord: s
+    .desc: staging
+  - dev.example.com
+branches: [main, dev, release]
+remotes: [origin, upstream]
+envs: [qa, prod]
+bad:
+  - [a, b]
+  - c
+sizes: [s, m]
+files: [.profile, .bashrc, .vimrc, .., profile]
+";
+        let (tree, violations) = parse(yml);
+        let hosts = tree.children()[&"hosts"];
+        assert_eq!(hosts.children()[&"1"].data().name, "prod.example.com");
+        assert_eq!(hosts.children()[&"s"].data().desc, "staging");
+        assert_eq!(hosts.children()[&"2"].data().value, "dev.example.com");
+        assert_eq!(tree.children()[&"branches"].children()[&"3"].data().value, "release");
+        // lists of two whose first item isn't a single key aren't [chord, value] pairs
+        let remotes = tree.children()[&"remotes"];
+        assert_eq!(remotes.children().len(), 2);
+        assert_eq!(remotes.children()[&"2"].data().value, "upstream");
+        assert_eq!(tree.children()[&"envs"].children().len(), 2);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].child_name, "bad");
+        assert_eq!(violations[1].child_name, "sizes");
+        // names of dotfiles don't read as reserved keys
+        let files = tree.children()[&"files"].children();
+        assert_eq!(files.len(), 5);
+        assert_eq!(files[&"1"].data().name, "profile");
+        assert_eq!(files[&"1"].data().value, ".profile");
+        assert_eq!(files[&"3"].data().name, "vimrc");
+        assert_eq!(files[&"4"].data().name, "4");
+        assert_eq!(files[&"5"].data().name, "profile (5)");
+    }
+
+    #[test]
+    fn test_scalars_are_coerced() {
+        let yml = "
+one:
+  .chord: 1
+  .value: true
+  .desc: 2.5
+  .exec: false
+two: [2, 42]
+3: three
+";
+        let (tree, violations) = parse(yml);
+        assert!(violations.is_empty());
+        let one = tree.children()[&"1"].data();
+        assert_eq!(one.value, "true");
+        assert_eq!(one.desc, "2.5");
+        assert!(!one.exec);
+        assert_eq!(tree.children()[&"2"].data().value, "42");
+        assert_eq!(tree.children()[&"3"].data().value, "three");
+    }
+
+    #[test]
+    fn test_unknown_reserved_keys() {
+        let yml = "
+git:
+  .chrod: g
+  .valeu: git
+  .foo: bar
+";
+        let (_, violations) = parse(yml);
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0].child_name, ".chrod");
+        assert!(violations[0].violation.ends_with("did you mean .chord?"));
+        assert!(violations[1].violation.ends_with("did you mean .value?"));
+        assert!(!violations[2].violation.contains("did you mean"));
+    }
+
     #[test]
     fn get_children_returns_children() {
     }
@@ -0,0 +1,213 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::tree::Tree;
+
+/// Argument of a value, written as `{?name}` or `{?name:default}`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+
+/// Piece of a value, either literal text or a placeholder to be filled in
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+
+/// Leaf whose placeholders are being asked for, the answers given so far
+/// and what has been typed for the current placeholder
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromptState<'a> {
+    pub leaf: &'a Tree,
+    pub placeholders: Vec<Placeholder>,
+    pub answers: Vec<String>,
+    pub input: String,
+}
+
+impl<'a> PromptState<'a> {
+    pub fn new(leaf: &'a Tree) -> Self {
+        Self {
+            leaf,
+            placeholders: placeholders(&leaf.data().value),
+            answers: Vec::new(),
+            input: String::new(),
+        }
+    }
+
+    /// Returns the placeholder currently asked for
+    pub fn current(&self) -> &Placeholder {
+        // a prompt is over once every placeholder has an answer
+        &self.placeholders[self.answers.len()]
+    }
+
+    /// Returns the description of the current placeholder given in `.args`
+    pub fn description(&self) -> Option<&'a str> {
+        let name = &self.current().name;
+        self.leaf.data().args.iter()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, desc)| desc.as_str())
+    }
+}
+
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Splits a value into text and placeholders. Only `{?name}` and
+/// `{?name:default}` are placeholders, every other brace is kept as written,
+/// such as those of `find -exec rm {} \;`, `awk '{print}'` or `{{.Field}}`.
+/// `${...}` is left untouched for the shell.
+fn parse_segments(value: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('$', Some('{')) => {
+                text.push('$');
+                for c in chars.by_ref() {
+                    text.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            },
+            ('{', Some('?')) => {
+                chars.next();
+                let placeholder = parse_placeholder(&mut chars)?;
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Placeholder(placeholder));
+            },
+            (c, _) => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Reads a placeholder after its `{?`, up to and including its `}`
+fn parse_placeholder(chars: &mut Peekable<Chars>) -> Result<Placeholder, String> {
+    let mut inner = String::new();
+    let mut closed = false;
+    for c in chars.by_ref() {
+        if c == '}' {
+            closed = true;
+            break;
+        }
+        inner.push(c);
+    }
+    if !closed {
+        return Err(format!("placeholder {{?{} is never closed", inner));
+    }
+    let (name, default) = match inner.split_once(':') {
+        Some((name, default)) => (name, Some(String::from(default))),
+        None => (inner.as_str(), None),
+    };
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return Err(format!("{{?{}}} is not a valid placeholder, names may only have letters, digits, _ and -", inner));
+    }
+    Ok(Placeholder { name: String::from(name), default })
+}
+
+/// Checks the placeholder syntax of a value
+pub fn validate(value: &str) -> Result<(), String> {
+    parse_segments(value).map(|_| ())
+}
+
+/// Returns the placeholders of a value, in order of first appearance.
+/// A placeholder used more than once is only returned once, with the
+/// first default given for it. Malformed values have no placeholders.
+pub fn placeholders(value: &str) -> Vec<Placeholder> {
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+    for segment in parse_segments(value).unwrap_or_default() {
+        if let Segment::Placeholder(placeholder) = segment {
+            match placeholders.iter_mut().find(|p| p.name == placeholder.name) {
+                Some(known) if known.default.is_none() => known.default = placeholder.default,
+                Some(_) => (),
+                None => placeholders.push(placeholder),
+            }
+        }
+    }
+    placeholders
+}
+
+/// Fills in the placeholders of a value with the answers given for them,
+/// in the order returned by `placeholders`. Placeholders without an answer
+/// take their default, or nothing. Values without placeholders, malformed
+/// ones included, are returned as written.
+pub fn substitute(value: &str, answers: &[String]) -> String {
+    let names = placeholders(value);
+    if names.is_empty() {
+        return String::from(value);
+    }
+    // values with placeholders parse
+    parse_segments(value).unwrap_or_default().into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text,
+            Segment::Placeholder(placeholder) => {
+                // every placeholder of the value is in names
+                let index = names.iter().position(|p| p.name == placeholder.name).unwrap();
+                answers.get(index)
+                    .or(names[index].default.as_ref())
+                    .cloned()
+                    .unwrap_or_default()
+            },
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let value = "git log {?branch:main} -- {?file} {?branch}";
+        assert_eq!(placeholders(value), vec![
+            Placeholder { name: String::from("branch"), default: Some(String::from("main")) },
+            Placeholder { name: String::from("file"), default: None },
+        ]);
+        assert_eq!(substitute(value, &[String::from("dev"), String::from("x")]), "git log dev -- x dev");
+        assert_eq!(substitute(value, &[]), "git log main --  main");
+        assert_eq!(substitute("awk '{print $1}' {?file}", &[String::from("x")]), "awk '{print $1}' x");
+        assert!(placeholders("echo ${HOME} ${?}").is_empty());
+    }
+
+    #[test]
+    fn test_other_braces_are_kept() {
+        let values = [
+            "docker inspect -f '{{.State.Status}}' {{ .Name }}",
+            "awk '{print}'",
+            "awk '{print $1}'",
+            "fzf --preview 'cat {1}' | parallel echo {1}",
+            "find . -name '*.orig' -exec rm {} \\;",
+            "cp file.{a,b} /tmp",
+            "echo }} {{",
+        ];
+        for value in values {
+            assert!(validate(value).is_ok());
+            assert!(placeholders(value).is_empty());
+            assert_eq!(substitute(value, &[]), value);
+        }
+    }
+
+    #[test]
+    fn test_malformed_placeholders() {
+        assert!(validate("git checkout {?branch").is_err());
+        assert!(validate("echo {?na me}").is_err());
+        assert!(validate("echo {?}").is_err());
+        assert!(validate("echo {?:main}").is_err());
+        assert_eq!(substitute("git checkout {?branch", &[]), "git checkout {?branch");
+    }
+}
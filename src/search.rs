@@ -0,0 +1,146 @@
+use super::tree::{Tree, LeafData};
+
+/// Bonus for a character matched right after the previous match
+const CONSECUTIVE_BONUS: i64 = 5;
+/// Bonus for a character matched at the start of a word
+const WORD_START_BONUS: i64 = 3;
+/// Maximum penalty for characters skipped before the first match
+const MAX_LEADING_PENALTY: i64 = 3;
+/// Bonus for matches on a tree's name, which is what users usually remember
+const NAME_BONUS: i64 = 10;
+
+
+/// Query typed by the user while searching and the result currently selected
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SearchState {
+    pub query: String,
+    pub selected: usize,
+}
+
+
+/// Leaf or node matched by a search
+#[derive(Debug, PartialEq)]
+pub struct SearchResult<'a> {
+    /// Trees from the searched tree (exclusive) down to the matched tree (inclusive)
+    pub path: Vec<&'a Tree>,
+    pub score: i64,
+}
+
+impl<'a> SearchResult<'a> {
+    /// Returns the tree matched by the search
+    pub fn tree(&self) -> &'a Tree {
+        // paths are never empty as a result is only built for a descendant
+        self.path.last().unwrap()
+    }
+}
+
+
+/// Scores how well `text` matches `query`.
+/// Every character of `query` must appear in `text`, in order but not necessarily
+/// adjacent, otherwise `None` is returned. Case is ignored.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    for c in query.to_lowercase().chars() {
+        let start = last_match.map_or(0, |last| last + 1);
+        let index = (start..text.len()).find(|&i| text[i] == c)?;
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        match last_match {
+            Some(last) if last + 1 == index => score += CONSECUTIVE_BONUS,
+            Some(last) => score -= (index - last - 1) as i64,
+            None => score -= (index as i64).min(MAX_LEADING_PENALTY),
+        }
+        last_match = Some(index);
+    }
+    Some(score)
+}
+
+/// Scores a tree by the best match among its name, description and value
+fn score_data(query: &str, data: &LeafData) -> Option<i64> {
+    [fuzzy_score(query, &data.name).map(|score| score + NAME_BONUS),
+     fuzzy_score(query, &data.desc),
+     fuzzy_score(query, &data.value)]
+        .into_iter()
+        .flatten()
+        .max()
+}
+
+/// Returns every leaf and node below `tree` matching `query`, best matches first.
+/// Trees with equal scores are kept in tree order, nodes before their children.
+pub fn search<'a>(tree: &'a Tree, query: &str) -> Vec<SearchResult<'a>> {
+    let mut results = Vec::new();
+    collect_results(tree, query, &mut Vec::new(), &mut results);
+    results.sort_by_key(|result| -result.score);
+    results
+}
+
+fn collect_results<'a>(tree: &'a Tree, query: &str, path: &mut Vec<&'a Tree>, results: &mut Vec<SearchResult<'a>>) {
+    if let Tree::Node(_, children) = tree {
+        // hidden trees are only reachable by chord
+        for child in children.iter().filter(|child| !child.data().hidden) {
+            path.push(child);
+            if let Some(score) = score_data(query, child.data()) {
+                results.push(SearchResult { path: path.clone(), score });
+            }
+            collect_results(child, query, path, results);
+            path.pop();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("gst", "git status").is_some());
+        assert!(fuzzy_score("GS", "git status").is_some());
+        assert!(fuzzy_score("sg", "git status").is_none());
+        assert!(fuzzy_score("stat", "git status") > fuzzy_score("stat", "set a tag"));
+    }
+
+    #[test]
+    fn test_search_returns_paths_to_trees() {
+        let yml = "
+git:
+  .chord: g
+  status:
+    .chord: s
+    .value: git status
+  log:
+    .chord: l
+    .desc: show history
+open:
+  .chord: o
+  zathura:
+    .chord: z
+  secret:
+    .chord: x
+    .hidden: true
+";
+        let (tree, _) = parser::parse(yml);
+        let results = search(&tree, "hist");
+        assert_eq!(results.len(), 1);
+        let chords = results[0].path.iter()
+            .map(|tree| tree.data().chord.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(chords, vec!["g", "l"]);
+
+        let results = search(&tree, "");
+        assert_eq!(results.len(), 5);
+        assert_eq!(search(&tree, "status")[0].tree().data().name, "status");
+        // nodes are results too
+        let results = search(&tree, "open");
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].tree().is_leaf());
+        assert!(search(&tree, "secret").is_empty());
+        assert!(tree.children()[&"o"].transition("x").is_some());
+    }
+}
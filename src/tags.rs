@@ -0,0 +1,116 @@
+use yaml_rust::Yaml;
+
+use super::tree::Tree;
+
+/// Reserved key with the tags of a node
+pub const TAGS_KEY: &str = ".tags";
+
+
+/// Reads `.tags`, either a tag or a list of tags
+pub fn from_yaml(node: &Yaml) -> Result<Vec<String>, String> {
+    let error = || format!("{} takes a tag or a list of tags, eg. [work, oncall]", TAGS_KEY);
+    match node {
+        Yaml::BadValue => Ok(Vec::new()),
+        Yaml::String(tag) => Ok(vec![tag.clone()]),
+        Yaml::Array(tags) => tags.iter()
+            .map(|tag| tag.as_str().map(String::from).ok_or_else(error))
+            .collect(),
+        _ => Err(error()),
+    }
+}
+
+
+/// Keeps the leaves tagged with any of `include`, if it isn't empty, and
+/// drops the leaves tagged with any of `exclude`. Trees have the tags of
+/// their ancestors too.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TagFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Drops the leaves which don't pass the filter, and the nodes left without leaves.
+    /// The root is always kept.
+    pub fn apply(&self, root: Tree) -> Tree {
+        if self.is_empty() {
+            return root;
+        }
+        let tags = root.data().tags.clone();
+        match root {
+            Tree::Node(data, children) => Tree::Node(data, self.filter_children(children, &tags)),
+            leaf => leaf,
+        }
+    }
+
+    /// `inherited` holds the tags of the children's ancestors
+    fn filter_children(&self, children: Vec<Tree>, inherited: &[String]) -> Vec<Tree> {
+        children.into_iter()
+            .filter_map(|child| {
+                let mut tags = inherited.to_vec();
+                tags.extend(child.data().tags.iter().cloned());
+                match child {
+                    Tree::Leaf(_) => Some(child).filter(|_| self.passes(&tags)),
+                    Tree::Node(data, children) => {
+                        let children = self.filter_children(children, &tags);
+                        Some(Tree::Node(data, children)).filter(|node| !node.children().is_empty())
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn passes(&self, tags: &[String]) -> bool {
+        let tagged = |filter: &[String]| filter.iter().any(|tag| tags.contains(tag));
+        (self.include.is_empty() || tagged(&self.include)) && !tagged(&self.exclude)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_filter_keeps_ancestors() {
+        let yml = "
+k8s:
+  .chord: k
+  .tags: work
+  pods: kubectl get pods
+  pager:
+    .value: pd incident list
+    .tags: [oncall]
+music:
+  .chord: m
+  play:
+    .value: mpc play
+    .tags: personal
+ls: ls
+";
+        let (tree, violations) = parser::parse(yml);
+        assert!(violations.is_empty());
+        let filter = TagFilter { include: vec![String::from("work")], exclude: vec![String::from("oncall")] };
+        let tree = filter.apply(tree);
+        assert_eq!(tree.children().len(), 1);
+        let k8s = tree.children()[&"k"];
+        assert_eq!(k8s.children().len(), 1);
+        assert_eq!(k8s.children()[&"pods"].data().value, "kubectl get pods");
+
+        let (tree, _) = parser::parse(yml);
+        let filter = TagFilter { include: Vec::new(), exclude: vec![String::from("work")] };
+        let tree = filter.apply(tree);
+        assert_eq!(tree.children().len(), 2);
+        assert!(tree.children().contains_key(&"ls"));
+
+        let (_, violations) = parser::parse("x:\n  .value: x\n  .tags: {work: true}\n");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].child_name, TAGS_KEY);
+    }
+}
@@ -0,0 +1,199 @@
+use yaml_rust::Yaml;
+use yaml_rust::yaml::Hash;
+
+use super::parser::Violation;
+use super::vars::Vars;
+
+/// Reserved key of the root declaring templates by name
+pub const TEMPLATES_KEY: &str = ".templates";
+/// Reserved key of a node instantiating a template
+pub const TEMPLATE_KEY: &str = ".template";
+/// Reserved key with the parameters of a template's instance
+pub const PARAMS_KEY: &str = ".params";
+
+
+/// Replaces every node with a `.template` by the children of that template,
+/// with `${param}` substituted in names, chords and values by the node's `.params`.
+/// The node's own children are kept and take precedence over the template's.
+/// Templates are declared in the root's `.templates`, which is dropped,
+/// a `.templates` anywhere else is reported by the parser.
+pub fn expand(root: &Yaml) -> (Yaml, Vec<Violation>) {
+    let templates = root[TEMPLATES_KEY].as_hash().cloned().unwrap_or_default();
+    let mut violations = Vec::new();
+    let root = match root {
+        Yaml::Hash(hash) => {
+            let mut hash = hash.clone();
+            hash.remove(&Yaml::String(String::from(TEMPLATES_KEY)));
+            Yaml::Hash(hash)
+        },
+        other => other.clone(),
+    };
+    let expanded = expand_node("root", &root, &templates, &mut Vec::new(), &mut violations);
+    (expanded, violations)
+}
+
+/// `instantiating` holds the templates being instantiated, to stop templates
+/// from instantiating themselves
+fn expand_node(name: &str, node: &Yaml, templates: &Hash, instantiating: &mut Vec<String>,
+               violations: &mut Vec<Violation>) -> Yaml {
+    let hash = match node {
+        Yaml::Hash(hash) => hash,
+        other => return other.clone(),
+    };
+    let violation = |violation: String| Violation {
+        parent_name: String::from(name),
+        child_name: String::from(TEMPLATE_KEY),
+        violation
+    };
+    let mut expanded = Hash::new();
+    if let Some(template_name) = hash.get(&Yaml::String(String::from(TEMPLATE_KEY))) {
+        match (template_name.as_str(), template_name.as_str().and_then(|t| templates.get(&Yaml::String(String::from(t))))) {
+            (Some(template_name), _) if instantiating.iter().any(|t| t == template_name) => {
+                violations.push(violation(format!("template {} instantiates itself", template_name)));
+            },
+            (Some(template_name), Some(template)) => {
+                let (params, invalid) = params(&node[PARAMS_KEY]);
+                if invalid {
+                    violations.push(Violation {
+                        parent_name: String::from(name),
+                        child_name: String::from(PARAMS_KEY),
+                        violation: String::from("parameters and their values must be strings")
+                    });
+                }
+                let instance = instantiate(template, &params);
+                instantiating.push(String::from(template_name));
+                if let Yaml::Hash(instance) = expand_node(name, &instance, templates, instantiating, violations) {
+                    expanded.extend(instance);
+                }
+                instantiating.pop();
+            },
+            (Some(template_name), None) => {
+                violations.push(violation(format!("template {} is not declared in the root's {}", template_name, TEMPLATES_KEY)));
+            },
+            (None, _) => violations.push(violation(String::from("the name of a template must be a string"))),
+        }
+    }
+    for (key, value) in hash.iter() {
+        match key.as_str() {
+            Some(TEMPLATE_KEY) | Some(PARAMS_KEY) => (),
+            Some(child_name) => {
+                let value = expand_node(child_name, value, templates, instantiating, violations);
+                expanded.insert(key.clone(), value);
+            },
+            None => {
+                expanded.insert(key.clone(), value.clone());
+            },
+        }
+    }
+    Yaml::Hash(expanded)
+}
+
+/// Reads the parameters of an instance. Also returns whether some were not strings.
+fn params(node: &Yaml) -> (Vars, bool) {
+    let mut invalid = false;
+    let params = node.as_hash()
+        .map(|params| params.iter()
+             .filter_map(|(key, value)| {
+                 let param = key.as_str().zip(value.as_str());
+                 invalid |= param.is_none();
+                 param.map(|(key, value)| (String::from(key), String::from(value)))
+             })
+             .collect())
+        .unwrap_or_default();
+    (Vars::from_pairs(params), invalid)
+}
+
+/// Copies a template, substituting its parameters in every key and string value
+fn instantiate(template: &Yaml, params: &Vars) -> Yaml {
+    match template {
+        Yaml::Hash(hash) => Yaml::Hash(hash.iter()
+            .map(|(key, value)| (instantiate(key, params), instantiate(value, params)))
+            .collect()),
+        Yaml::Array(array) => Yaml::Array(array.iter().map(|value| instantiate(value, params)).collect()),
+        Yaml::String(text) => Yaml::String(params.substitute(text)),
+        other => other.clone(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parser;
+
+    #[test]
+    fn test_templates_are_instantiated() {
+        let yml = "
+.templates:
+  service:
+    ${service}-logs:
+      .chord: l
+      .run: kubectl logs deploy/${service}
+    restart:
+      .chord: r
+      .run: kubectl rollout restart deploy/${service}
+api:
+  .chord: a
+  .template: service
+  .params:
+    service: api
+  restart:
+    .chord: r
+    .run: systemctl restart api
+db:
+  .chord: d
+  .template: service
+  .params:
+    service: db
+";
+        let (tree, violations) = parser::parse(yml);
+        assert!(violations.is_empty());
+        assert_eq!(tree.children().len(), 2);
+        let api = tree.children()[&"a"];
+        assert_eq!(api.children()[&"l"].data().name, "api-logs");
+        assert_eq!(api.children()[&"l"].data().value, "kubectl logs deploy/api");
+        assert_eq!(api.children()[&"r"].data().value, "systemctl restart api");
+        let db = tree.children()[&"d"];
+        assert_eq!(db.children()[&"r"].data().value, "kubectl rollout restart deploy/db");
+    }
+
+    #[test]
+    fn test_template_violations() {
+        let yml = "
+.templates:
+  loop:
+    again:
+      .template: loop
+x:
+  .template: loop
+y:
+  .template: missing
+";
+        let (_, violations) = parser::parse(yml);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].parent_name, "again");
+        assert_eq!(violations[1].parent_name, "y");
+    }
+
+    #[test]
+    fn test_nested_templates_are_violations() {
+        let yml = "
+git:
+  .chord: g
+  .templates:
+    remote:
+      push: git push ${remote}
+  .anchors:
+    base: &base
+      .value: git status
+  status: *base
+";
+        let (tree, violations) = parser::parse(yml);
+        let git = tree.children()[&"g"];
+        assert_eq!(git.children().len(), 1);
+        assert_eq!(git.children()[&"status"].data().value, "git status");
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().all(|violation| violation.parent_name == "git"));
+        assert_eq!(violations[0].child_name, ".templates");
+        assert_eq!(violations[1].child_name, ".anchors");
+    }
+}
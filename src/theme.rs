@@ -0,0 +1,290 @@
+use std::fmt;
+use std::str::FromStr;
+
+use termion::color;
+
+
+/// Foreground color, written as a name (eg. `red`, `light-blue`),
+/// a 256 color index or `#rrggbb`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Color {
+    /// Index in the 256 color palette
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+    /// The terminal's default color
+    Reset,
+}
+
+impl Color {
+    pub const NAMES: &'static [&'static str] = &[
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("{} is not a color, use one of {}, light-<color>, default, a number up to 255 or #rrggbb",
+                               s, Color::NAMES.join(", "));
+        let name = s.to_lowercase();
+        if name == "default" {
+            return Ok(Color::Reset);
+        }
+        if let Some(index) = Color::NAMES.iter().position(|color| *color == name) {
+            return Ok(Color::Ansi(index as u8));
+        }
+        if let Some(index) = name.strip_prefix("light-").and_then(|name| Color::NAMES.iter().position(|color| *color == name)) {
+            return Ok(Color::Ansi(index as u8 + 8));
+        }
+        if let Some(hex) = name.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())) {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| error());
+            return Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        name.parse().map(Color::Ansi).map_err(|_| error())
+    }
+}
+
+impl fmt::Display for Color {
+    /// Writes the escape code which sets the foreground to this color
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // the 16 basic colors use their own codes, which every terminal knows
+            Color::Ansi(index) if *index < 8 => write!(f, "\x1b[3{}m", index),
+            Color::Ansi(index) if *index < 16 => write!(f, "\x1b[9{}m", index - 8),
+            Color::Ansi(index) => write!(f, "{}", color::Fg(color::AnsiValue(*index))),
+            Color::Rgb(r, g, b) => write!(f, "{}", color::Fg(color::Rgb(*r, *g, *b))),
+            Color::Reset => write!(f, "{}", color::Fg(color::Reset)),
+        }
+    }
+}
+
+
+/// Color and attributes of a piece of text, written as words separated by
+/// spaces, eg. `bold light-blue` or `underline`
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+    pub italic: bool,
+    /// Swaps the foreground and background colors
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const ATTRIBUTES: &'static [&'static str] = &["bold", "dim", "underline", "italic", "reverse"];
+
+    pub fn color(color: Color) -> Self {
+        Self { color: Some(color), ..Default::default() }
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::default();
+        for word in s.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "underline" => style.underline = true,
+                "italic" => style.italic = true,
+                "reverse" => style.reverse = true,
+                _ if style.color.is_some() => return Err(format!("{} has more than one color", s)),
+                _ => style.color = Some(word.parse().map_err(|err| format!("{}, or an attribute: {}", err, Style::ATTRIBUTES.join(", ")))?),
+            }
+        }
+        Ok(style)
+    }
+}
+
+
+/// Styles used by the interface
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Chords of the choices, or their keys left to type
+    pub chord: Style,
+    /// Keys of the choices' chords which were already typed
+    pub matched: Style,
+    /// Names of the choices, unless they have a `.color`
+    pub name: Style,
+    /// Descriptions of the search results
+    pub desc: Style,
+    /// Header bar with the path to the current node
+    pub breadcrumb: Style,
+    /// Keys typed which don't complete a chord yet
+    pub typed: Style,
+    /// Selected search result
+    pub selected: Style,
+    /// Dead ends, questions and other messages to the user
+    pub status: Style,
+    /// Chord picked unless more keys are typed, see `--timeout`
+    pub pending: Style,
+    /// Whether colors are written, see `for_terminal`
+    colors: bool,
+    /// Whether attributes such as bold are written
+    attributes: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            chord: Style::color(Color::Ansi(1)),
+            matched: Style { dim: true, underline: true, ..Default::default() },
+            name: Style::default(),
+            desc: Style::default(),
+            breadcrumb: Style { reverse: true, ..Default::default() },
+            typed: Style { underline: true, ..Default::default() },
+            selected: Style { reverse: true, ..Default::default() },
+            status: Style::color(Color::Ansi(1)),
+            pending: Style { italic: true, ..Default::default() },
+            colors: true,
+            attributes: true,
+        }
+    }
+}
+
+impl Theme {
+    pub const NAMES: &'static [&'static str] = &["chord", "matched", "name", "desc", "breadcrumb", "typed", "selected", "status", "pending"];
+
+    /// Sets the style of the element called `name`
+    pub fn set(&mut self, name: &str, style: Style) -> Result<(), String> {
+        match name {
+            "chord" => self.chord = style,
+            "matched" => self.matched = style,
+            "name" => self.name = style,
+            "desc" => self.desc = style,
+            "breadcrumb" => self.breadcrumb = style,
+            "typed" => self.typed = style,
+            "selected" => self.selected = style,
+            "status" => self.status = style,
+            "pending" => self.pending = style,
+            _ => return Err(format!("{} can't be styled, use one of {}", name, Theme::NAMES.join(", "))),
+        }
+        Ok(())
+    }
+
+    /// Returns a theme which doesn't write any escape code
+    pub fn plain() -> Self {
+        Self { colors: false, attributes: false, ..Default::default() }
+    }
+
+    /// Drops colors when `$NO_COLOR` is set and every style when `$TERM` is `dumb`,
+    /// see https://no-color.org
+    pub fn for_terminal(mut self) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+        self.colors = !no_color && !dumb;
+        self.attributes = !dumb;
+        self
+    }
+
+    /// Wraps `text` with the escape codes for `style`. Styles can be nested:
+    /// what ends an inner style may also undo the outer one (eg. normal
+    /// intensity undoes both bold and dim), so the outer style is set again
+    /// after every inner one.
+    pub fn paint(&self, style: &Style, text: &str) -> String {
+        let mut start = String::new();
+        let mut end = String::new();
+        if let Some(color) = style.color.as_ref().filter(|_| self.colors) {
+            start.push_str(&color.to_string());
+            end.push_str(&color::Fg(color::Reset).to_string());
+        }
+        if self.attributes {
+            // NoBold is double underline on some terminals, normal intensity undoes bold too
+            let attributes = [
+                (style.bold, termion::style::Bold.to_string(), termion::style::NoFaint.to_string()),
+                (style.dim, termion::style::Faint.to_string(), termion::style::NoFaint.to_string()),
+                (style.underline, termion::style::Underline.to_string(), termion::style::NoUnderline.to_string()),
+                (style.italic, termion::style::Italic.to_string(), termion::style::NoItalic.to_string()),
+                (style.reverse, termion::style::Invert.to_string(), termion::style::NoInvert.to_string()),
+            ];
+            for (_, set, unset) in attributes.iter().filter(|(enabled, _, _)| *enabled) {
+                start.push_str(set);
+                end.push_str(unset);
+            }
+        }
+        if start.is_empty() {
+            return String::from(text);
+        }
+        format!("{}{}{}", start, restore_after_resets(text, &start), end)
+    }
+}
+
+
+/// Writes `start` again after every run of the codes which end a style in
+/// `text`, unless the text ends there
+fn restore_after_resets(text: &str, start: &str) -> String {
+    let resets = [
+        color::Fg(color::Reset).to_string(),
+        termion::style::NoFaint.to_string(),
+        termion::style::NoUnderline.to_string(),
+        termion::style::NoItalic.to_string(),
+        termion::style::NoInvert.to_string(),
+    ];
+    let reset_at = |text: &str| resets.iter().find(|reset| text.starts_with(reset.as_str())).map(String::len);
+    let mut restored = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match reset_at(rest) {
+            Some(length) => {
+                restored.push_str(&rest[..length]);
+                rest = &rest[length..];
+                if !rest.is_empty() && reset_at(rest).is_none() {
+                    restored.push_str(start);
+                }
+            },
+            None => {
+                restored.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    restored
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colors() {
+        assert_eq!("red".parse(), Ok(Color::Ansi(1)));
+        assert_eq!("Light-Blue".parse(), Ok(Color::Ansi(12)));
+        assert_eq!("208".parse(), Ok(Color::Ansi(208)));
+        assert_eq!("#ff8000".parse(), Ok(Color::Rgb(255, 128, 0)));
+        assert!("purple".parse::<Color>().is_err());
+        assert!("#ff80".parse::<Color>().is_err());
+        assert!("#aébbc".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_styles() {
+        let style = "bold light-blue".parse::<Style>().unwrap();
+        assert_eq!(style, Style { color: Some(Color::Ansi(12)), bold: true, ..Default::default() });
+        assert!("red blue".parse::<Style>().is_err());
+        assert!("blink".parse::<Style>().is_err());
+        assert_eq!("italic".parse::<Style>().unwrap(), Style { italic: true, ..Default::default() });
+
+        let mut theme = Theme::default();
+        assert_eq!(theme.paint(&style, "x"), "\x1b[94m\x1b[1mx\x1b[39m\x1b[22m");
+        theme.colors = false;
+        assert_eq!(theme.paint(&style, "x"), "\x1b[1mx\x1b[22m");
+        theme.attributes = false;
+        assert_eq!(theme.paint(&style, "x"), "x");
+    }
+
+    #[test]
+    fn test_nested_styles() {
+        let theme = Theme::default();
+        let inner = theme.paint(&"red bold".parse().unwrap(), "a");
+        let outer = theme.paint(&"dim reverse".parse().unwrap(), &format!("{}b", inner));
+        // dim and reverse are set again once the inner style is over
+        assert_eq!(outer, "\x1b[2m\x1b[7m\x1b[31m\x1b[1ma\x1b[39m\x1b[22m\x1b[2m\x1b[7mb\x1b[22m\x1b[27m");
+        assert_eq!(theme.paint(&Style::default(), &inner), inner);
+    }
+}
@@ -1,13 +1,38 @@
 use std::collections::HashMap;
 use std::fmt;
 
+use super::chord;
+use super::condition::Condition;
+
 ///Encapsulates the data stored by a leaf
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Default)]
 pub struct LeafData {
     pub name: String,
     pub desc: String,
     pub chord: String,
-    pub value: String
+    pub value: String,
+    /// Whether value is a command pickem should run once picked
+    pub exec: bool,
+    /// Working directory for the command
+    pub cwd: Option<String>,
+    /// Environment variables set for the command
+    pub env: Vec<(String, String)>,
+    /// Descriptions of the placeholders in value, by placeholder name
+    pub args: Vec<(String, String)>,
+    /// Question asked before the leaf is picked, if it has to be confirmed
+    pub confirm: Option<String>,
+    /// Style of the name in the list of choices, eg. `bold green`
+    pub color: Option<String>,
+    /// Shown before the name in the list of choices
+    pub icon: Option<String>,
+    /// Whether the tree is left out of the list of choices, it's still reachable by chord
+    pub hidden: bool,
+    /// Conditions under which the tree is kept, see `condition::prune`
+    pub when: Vec<Condition>,
+    /// Tags used to filter the tree, see `tags::TagFilter`
+    pub tags: Vec<String>,
+    /// Names on the path to the leaf this one is a copy of, see `Frecency::with_recent`
+    pub origin: Option<Vec<String>>,
 }
 
 
@@ -31,20 +56,24 @@ impl Tree {
 
     ///Returns map of children 1st level transitions for a tree.
     pub fn children(&self) -> HashMap<&str, &Tree> {
-        self.transitions_by_prefix(&"")
+        self.transitions_by_prefix("")
     }
 
 
     ///Attempts to return a child of `Tree` whose chord is `chord`.
+    ///Chords are compared key by key, so `<C-g>` matches `<c-g>`.
     pub fn transition(&self, chord: &str) -> Option<&Tree> {
-        let transitions = self.children();
-        match transitions.get(chord) {
-            Option::Some(tree) => Option::Some(*tree),
-            Option::None => Option::None
+        let keys = chord::parse_lossy(chord);
+        match self {
+            Tree::Leaf(_) => None,
+            Tree::Node(_, children) => children.iter()
+                .find(|child| chord::parse_lossy(&child.data().chord) == keys)
         }
     }
 
+    ///Returns map of children whose chord starts with the keys in `prefix`.
     pub fn transitions_by_prefix(&self, prefix: &str) -> HashMap<&str, &Tree> {
+        let prefix = chord::parse_lossy(prefix);
         match self {
             Tree::Leaf(_) => HashMap::new(),
             Tree::Node(_, children) => {
@@ -52,7 +81,7 @@ impl Tree {
                 for child in children {
                     let data = child.data();
                     let chord = data.chord.as_str();
-                    if chord.starts_with(prefix) {
+                    if chord::parse_lossy(chord).starts_with(&prefix) {
                         map.insert(chord, child);
                     }
 
@@ -63,10 +92,7 @@ impl Tree {
     }
 
     pub fn is_leaf(&self) -> bool {
-        match *self {
-            Tree::Leaf(_) => true,
-            _ => false
-        }
+        matches!(*self, Tree::Leaf(_))
     }
 
     pub fn format_tree(tree: &Tree, offset: usize) -> String {
@@ -86,11 +112,52 @@ impl Tree {
 
     fn indentable_data(data: &LeafData, leading_spaces: usize) -> String {
         let indent = String::from(" ").repeat(leading_spaces);
-        let name = format!("{}{}:", indent, data.name);
-        let chord = format!("{}.chord: {}", indent, data.chord);
-        let desc = format!("{}.desc: {}", indent, data.desc);
-        let value = format!("{}.value: {}\n", indent, data.value);
-        vec![name, chord, desc, value].join("\n  ")
+        let mut lines = vec![
+            format!("{}{}:", indent, data.name),
+            format!("{}.chord: {}", indent, data.chord),
+            format!("{}.desc: {}", indent, data.desc),
+            format!("{}.value: {}", indent, data.value),
+        ];
+        if data.exec {
+            lines.push(format!("{}.exec: true", indent));
+        }
+        if let Some(cwd) = &data.cwd {
+            lines.push(format!("{}.cwd: {}", indent, cwd));
+        }
+        if !data.env.is_empty() {
+            lines.push(format!("{}.env:", indent));
+            for (key, value) in data.env.iter() {
+                lines.push(format!("{}  {}: {}", indent, key, value));
+            }
+        }
+        if let Some(confirm) = &data.confirm {
+            lines.push(format!("{}.confirm: {}", indent, confirm));
+        }
+        if let Some(color) = &data.color {
+            lines.push(format!("{}.color: {}", indent, color));
+        }
+        if let Some(icon) = &data.icon {
+            lines.push(format!("{}.icon: {}", indent, icon));
+        }
+        if data.hidden {
+            lines.push(format!("{}.hidden: true", indent));
+        }
+        if !data.when.is_empty() {
+            lines.push(format!("{}.when:", indent));
+            for condition in data.when.iter() {
+                lines.push(format!("{}  {}", indent, condition.to_yaml()));
+            }
+        }
+        if !data.tags.is_empty() {
+            lines.push(format!("{}.tags: [{}]", indent, data.tags.join(", ")));
+        }
+        if !data.args.is_empty() {
+            lines.push(format!("{}.args:", indent));
+            for (name, desc) in data.args.iter() {
+                lines.push(format!("{}  {}: {}", indent, name, desc));
+            }
+        }
+        format!("{}\n", lines.join("\n  "))
     }
 }
 
@@ -106,13 +173,13 @@ mod tests {
     use super::*;
 
     fn data_builder(param: String) -> LeafData {
-        let data = LeafData {
+        LeafData {
             name: param.clone(),
             desc: param.clone(),
             chord: param.clone(),
-            value: param.clone(),
-        };
-        return data;
+            value: param,
+            ..Default::default()
+        }
     }
 
     #[test]
@@ -1,14 +1,10 @@
-use super::tree::{Tree, LeafData};
-use termion::color;
-
-/// Converts the selected trees and lingering characters into a
-/// representative string.
-pub fn pprint_user_input(trees: &Vec<&Tree>, input_buffer: &str) -> String {
-    let chords_selected = trees.iter().map(|tree| tree.data().chord.as_str()).collect::<Vec<&str>>();
-    let user_input = chords_selected.join(" > ");
-    format!("{} > {}", user_input, input_buffer)
-}
+use std::process::ExitStatus;
 
+use super::tree::Tree;
+use super::search::SearchResult;
+use super::placeholder::PromptState;
+use super::chord;
+use super::theme::{Style, Theme};
 
 ///Returns formatted string with the name of the selected trees
 ///separated by " > ".
@@ -19,32 +15,139 @@ pub fn pprint_nodes(trees: &Vec<&Tree>) -> String {
         .join(" > ")
 }
 
-///Returns string of a choice formatted with colors for the terminal
-pub fn pprint_choice(tree: &Tree) -> String {
+///Returns the header bar: the path from root to the selected trees, every
+///tree prefixed by its depth and followed by its chord, then the keys typed
+///so far. eg. " 0:root > 1:programs p > 2:screenshots s > a "
+///The bar is padded to `width`, the trees closest to the root (other than the root)
+///are left out when it doesn't fit, then the bar is cut.
+pub fn pprint_header(theme: &Theme, root: &Tree, trees: &[&Tree], input_buffer: &str, width: Option<usize>) -> String {
+    // plain text, to measure the segments, and styled text
+    let mut segments = vec![(format!("0:{}", root.data().name), format!("0:{}", root.data().name))];
+    for (depth, tree) in trees.iter().enumerate() {
+        let data = tree.data();
+        let chord = chord::readable(&data.chord);
+        segments.push((format!("{}:{} {}", depth + 1, data.name, chord),
+                       format!("{}:{} {}", depth + 1, data.name, theme.paint(&theme.chord, &chord))));
+    }
+    if !input_buffer.is_empty() {
+        let typed = chord::readable(input_buffer);
+        segments.push((typed.clone(), theme.paint(&theme.typed, &typed)));
+    }
+    let join = |segments: &[(String, String)], styled: bool| {
+        let texts = segments.iter()
+            .map(|(plain, painted)| if styled { painted.as_str() } else { plain.as_str() })
+            .collect::<Vec<_>>();
+        format!(" {} ", texts.join(" > "))
+    };
+    let width = match width {
+        Some(width) => width,
+        None => return theme.paint(&theme.breadcrumb, &join(&segments, true)),
+    };
+    let ellipsis = (String::from("…"), String::from("…"));
+    let mut hidden = 0;
+    let bar = loop {
+        let mut shown = segments[..1].to_vec();
+        if hidden > 0 {
+            shown.push(ellipsis.clone());
+        }
+        shown.extend_from_slice(&segments[1 + hidden..]);
+        let length = join(&shown, false).chars().count();
+        if length <= width {
+            break format!("{}{}", join(&shown, true), " ".repeat(width - length));
+        }
+        if 1 + hidden + 1 >= segments.len() {
+            // even the root and the last segment don't fit
+            let plain = join(&shown, false).chars().take(width.saturating_sub(1)).collect::<String>();
+            break format!("{}…", plain).chars().take(width).collect();
+        }
+        hidden += 1;
+    };
+    theme.paint(&theme.breadcrumb, &bar)
+}
+
+///Returns the name of a tree preceded by its icon, in the style of its
+///`.color` or the theme's
+pub fn pprint_name(theme: &Theme, tree: &Tree) -> String {
     let data = tree.data();
-    format!("{}{}{} - {}",
-           color::Fg(color::Red),
-           data.chord,
-           color::Fg(color::Reset),
-           data.name)
+    let style = data.color.as_deref()
+        .and_then(|color| color.parse::<Style>().ok())
+        .unwrap_or_else(|| theme.name.clone());
+    let name = theme.paint(&style, &data.name);
+    match &data.icon {
+        Some(icon) => format!("{} {}", icon, name),
+        None => name,
+    }
+}
+
+///Returns string of a choice formatted with colors for the terminal.
+///The first `typed` keys of its chord are set apart from the keys left to type.
+pub fn pprint_choice(theme: &Theme, tree: &Tree, typed: usize) -> String {
+    let data = tree.data();
+    let (matched, rest) = chord::readable_split(&data.chord, typed);
+    let chord = [(&theme.matched, matched), (&theme.chord, rest)].iter()
+        .filter(|(_, keys)| !keys.is_empty())
+        .map(|(style, keys)| theme.paint(style, keys))
+        .collect::<String>();
+    format!("{} - {}", chord, pprint_name(theme, tree))
+}
+
+///Returns string telling the user a chord is pending
+pub fn pprint_pending(theme: &Theme, tree: &Tree) -> String {
+    theme.paint(&theme.pending, &format!("{} pending, keep typing for longer chords", tree.data().name))
+}
+
+///Returns the question for the current placeholder of a prompt followed
+///by the answer typed so far, eg. "branch (branch to check out) [main]: dev"
+pub fn pprint_prompt(theme: &Theme, state: &PromptState) -> String {
+    let placeholder = state.current();
+    let mut question = placeholder.name.clone();
+    if let Some(desc) = state.description() {
+        question.push_str(&format!(" ({})", desc));
+    }
+    if let Some(default) = &placeholder.default {
+        question.push_str(&format!(" [{}]", default));
+    }
+    format!("{}: {}", theme.paint(&theme.status, &question), state.input)
+}
+
+///Returns the question asked before picking `tree`, eg. "Discard changes? [y/N]"
+pub fn pprint_confirmation(theme: &Theme, tree: &Tree) -> String {
+    format!("{} [y/N]", theme.paint(&theme.status, tree.data().confirm.as_deref().unwrap_or_default()))
+}
+
+///Explains why the keys typed at `head` lead nowhere
+pub fn dead_end_message(typed: &str, head: &Tree) -> String {
+    format!("{}: no chord in {} starts with these keys", chord::readable(typed), head.data().name)
+}
+
+///Describes how the command of `tree` finished
+pub fn exit_message(tree: &Tree, status: ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("{} exited with status {}", tree.data().name, code),
+        None => format!("{} was terminated by a signal", tree.data().name),
+    }
+}
+
+///Returns an error message formatted with colors for the terminal
+pub fn pprint_error(theme: &Theme, message: &str) -> String {
+    theme.paint(&theme.status, message)
+}
+
+///Returns string of a search result with the chords needed to reach it
+///followed by its name and description
+pub fn pprint_search_result(theme: &Theme, result: &SearchResult) -> String {
+    let chords = result.path.iter()
+        .map(|tree| chord::readable(&tree.data().chord))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let desc = format!("({})", result.tree().data().desc);
+    format!("{} - {} {}", theme.paint(&theme.chord, &chords), pprint_name(theme, result.tree()), theme.paint(&theme.desc, &desc))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_pprint_user_input() {
-        let data = LeafData {
-            name: String::from("name"),
-            desc: String::from("desc"),
-            chord: String::from("chord"),
-            value: String::from("value"),
-        };
-        let tree = Tree::Leaf(data);
-        let trees: Vec<&Tree> = vec![&tree];
-        assert_eq!(pprint_user_input(&trees, &"a"), String::from("chord > a"));
-    }
+    use crate::tree::LeafData;
 
     #[test]
     fn test_pprint_nodes() {
@@ -53,16 +156,44 @@ mod tests {
             desc: String::from("root"),
             chord: String::from("root"),
             value: String::from("root"),
+            ..Default::default()
         };
         let d2 = LeafData {
             name: String::from("programs"),
             desc: String::from("programs"),
             chord: String::from("programs"),
             value: String::from("programs"),
+            ..Default::default()
         };
         let root = Tree::Leaf(d1);
         let programs = Tree::Leaf(d2);
         let trees: Vec<&Tree> = vec![&root, &programs];
         assert_eq!(pprint_nodes(&trees), String::from("root > programs"));
     }
+
+    #[test]
+    fn test_pprint_header() {
+        let tree = |name: &str, chord: &str| Tree::Leaf(LeafData {
+            name: String::from(name),
+            chord: String::from(chord),
+            ..Default::default()
+        });
+        let (root, programs, screenshots) = (tree("root", ""), tree("programs", "p"), tree("screenshots", "<C-s>"));
+        let trees = vec![&programs, &screenshots];
+        let theme = Theme::plain();
+        assert_eq!(pprint_header(&theme, &root, &trees, "a", None),
+                   " 0:root > 1:programs p > 2:screenshots Ctrl+s > a ");
+        assert_eq!(pprint_header(&theme, &root, &trees, "", Some(50)),
+                   " 0:root > 1:programs p > 2:screenshots Ctrl+s     ");
+        assert_eq!(pprint_header(&theme, &root, &trees, "a", Some(40)),
+                   " 0:root > … > 2:screenshots Ctrl+s > a  ");
+        assert_eq!(pprint_header(&theme, &root, &trees, "a", Some(10)), " 0:root >…");
+    }
+
+    #[test]
+    fn test_pprint_pending() {
+        let tree = Tree::Leaf(LeafData { name: String::from("log"), ..Default::default() });
+        assert_eq!(pprint_pending(&Theme::plain(), &tree), "log pending, keep typing for longer chords");
+        assert_eq!(pprint_pending(&Theme::default(), &tree), "\x1b[3mlog pending, keep typing for longer chords\x1b[23m");
+    }
 }
@@ -0,0 +1,210 @@
+use yaml_rust::Yaml;
+
+/// Variables available to `${name}` in a menu: the ones declared in the
+/// root's `.vars`, then the environment's
+#[derive(Debug, Default)]
+pub struct Vars {
+    vars: Vec<(String, String)>,
+}
+
+impl Vars {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the variables declared in `.vars`. Values are expanded in
+    /// order, so they may use the environment and variables declared before them.
+    /// Returns the variables which could be read and an error for each one which couldn't.
+    pub fn from_yaml(node: &Yaml) -> (Self, Vec<(String, String)>) {
+        let mut vars = Self::new();
+        let mut errors = Vec::new();
+        if let Some(hash) = node.as_hash() {
+            for (key, value) in hash.iter() {
+                let name = match key.as_str() {
+                    Some(name) => String::from(name),
+                    None => {
+                        errors.push((format!("{:?}", key), String::from("variable names must be strings")));
+                        continue;
+                    }
+                };
+                match value.as_str() {
+                    Some(value) => {
+                        for undefined in vars.undefined(value) {
+                            errors.push((name.clone(), undefined_message(&undefined)));
+                        }
+                        let value = vars.expand(value);
+                        vars.vars.push((name, value));
+                    },
+                    None => errors.push((name, String::from("variable values must be strings"))),
+                }
+            }
+        }
+        (vars, errors)
+    }
+
+    /// Returns variables with the given values, eg. the parameters of a template
+    pub fn from_pairs(vars: Vec<(String, String)>) -> Self {
+        Self { vars }
+    }
+
+    /// Replaces every `${name}` in `text` whose variable was declared.
+    /// Anything else, including `$${` and undeclared variables, is kept for `expand`.
+    pub fn substitute(&self, text: &str) -> String {
+        let mut substituted = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            substituted.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if rest.starts_with("$$") {
+                substituted.push_str("$$");
+                rest = &rest[2..];
+                continue;
+            }
+            let declared = rest.strip_prefix("${")
+                .and_then(|inner| inner.split_once('}'))
+                .and_then(|(name, after)| self.vars.iter()
+                          .rev()
+                          .find(|(var, _)| var == name)
+                          .map(|(_, value)| (value, after)));
+            match declared {
+                Some((value, after)) => {
+                    substituted.push_str(value);
+                    rest = after;
+                },
+                None => {
+                    substituted.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        substituted.push_str(rest);
+        substituted
+    }
+
+    /// Returns the value of a variable, looking it up in the environment
+    /// if it wasn't declared
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.vars.iter()
+            .rev()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(name).ok())
+    }
+
+    /// Replaces every `${name}` in `text` whose variable is declared or in
+    /// the environment by its value, and every `$${` by `${`. Anything else,
+    /// such as `$$` or the `${...}` of shell variables, is kept as is.
+    pub fn expand(&self, text: &str) -> String {
+        let mut expanded = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix("$${") {
+                expanded.push_str("${");
+                rest = after;
+                continue;
+            }
+            let defined = rest.strip_prefix("${")
+                .and_then(|inner| inner.split_once('}'))
+                .and_then(|(name, after)| self.get(name).map(|value| (value, after)));
+            match defined {
+                Some((value, after)) => {
+                    expanded.push_str(&value);
+                    rest = after;
+                },
+                None => {
+                    expanded.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Returns the names of the `${name}` in `text` which are neither declared
+    /// nor in the environment. `$${name}` and `${...}` which aren't plain names,
+    /// such as `${1}` or `${name:-default}`, are meant for the shell.
+    pub fn undefined(&self, text: &str) -> Vec<String> {
+        let mut undefined = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix("$${") {
+                rest = after;
+                continue;
+            }
+            let name = rest.strip_prefix("${")
+                .and_then(|inner| inner.split_once('}'))
+                .map(|(name, _)| name)
+                .filter(|name| is_name(name));
+            if let Some(name) = name {
+                if self.get(name).is_none() && !undefined.iter().any(|var| var == name) {
+                    undefined.push(String::from(name));
+                }
+            }
+            rest = &rest[1..];
+        }
+        undefined
+    }
+}
+
+/// Whether `${text}` names a variable rather than being shell syntax
+fn is_name(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Explains that a variable is neither declared nor in the environment
+pub fn undefined_message(name: &str) -> String {
+    format!("${{{}}} is neither declared in .vars nor in the environment, write $${{{}}} to leave it to the shell", name, name)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_expand() {
+        let yml = "
+projects: ${HOME}/projects
+pickem: ${projects}/pickem
+bad: [1, 2]
+";
+        let node = &YamlLoader::load_from_str(yml).unwrap()[0];
+        let (vars, errors) = Vars::from_yaml(node);
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(vars.expand("cd ${pickem}"), format!("cd {}/projects/pickem", home));
+        assert_eq!(vars.expand("echo $USER $${HOME} ${projects"), "echo $USER ${HOME} ${projects");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "bad");
+    }
+
+    #[test]
+    fn test_expand_keeps_shell_syntax() {
+        let vars = Vars::new();
+        assert_eq!(vars.expand("kill $$"), "kill $$");
+        assert_eq!(vars.expand("echo $$ $5"), "echo $$ $5");
+        assert_eq!(vars.expand("echo ${PICKEM_UNDEFINED_VARIABLE:-none}"), "echo ${PICKEM_UNDEFINED_VARIABLE:-none}");
+    }
+
+    #[test]
+    fn test_undefined() {
+        let vars = Vars::from_pairs(vec![(String::from("service"), String::from("api"))]);
+        let text = "${service} ${HOME} ${PICKEM_UNDEFINED_VARIABLE} $${PICKEM_UNDEFINED_VARIABLE} ${1} ${x:-y} $$";
+        assert_eq!(vars.undefined(text), vec![String::from("PICKEM_UNDEFINED_VARIABLE")]);
+        let (_, errors) = Vars::from_yaml(&YamlLoader::load_from_str("bad: ${PICKEM_UNDEFINED_VARIABLE}/x").unwrap()[0]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "bad");
+    }
+
+    #[test]
+    fn test_substitute_only_declared() {
+        let vars = Vars::from_pairs(vec![(String::from("service"), String::from("api"))]);
+        assert_eq!(vars.substitute("logs ${service} $${service} ${HOME} $5"), "logs api $${service} ${HOME} $5");
+    }
+}